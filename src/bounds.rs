@@ -0,0 +1,172 @@
+use crate::floats::EPSILON;
+use crate::matrices::Matrix4;
+use crate::rays::Ray;
+use crate::tuples::{Point, Tuple};
+
+// An axis-aligned bounding box
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Bounds {
+    pub min: Point,
+    pub max: Point,
+}
+
+impl Bounds {
+    pub fn new(min: Point, max: Point) -> Bounds {
+        Bounds { min, max }
+    }
+
+    // A box containing nothing, which grows to fit whatever is added to it
+    pub fn empty() -> Bounds {
+        Bounds {
+            min: Point::new(f64::INFINITY, f64::INFINITY, f64::INFINITY),
+            max: Point::new(f64::NEG_INFINITY, f64::NEG_INFINITY, f64::NEG_INFINITY),
+        }
+    }
+
+    pub fn add_point(&mut self, point: Point) {
+        self.min = self.min.min(&point);
+        self.max = self.max.max(&point);
+    }
+
+    pub fn merge(&self, other: &Bounds) -> Bounds {
+        let mut merged = *self;
+        merged.add_point(other.min);
+        merged.add_point(other.max);
+        merged
+    }
+
+    pub fn contains_point(&self, point: Point) -> bool {
+        (self.min.x..=self.max.x).contains(&point.x)
+            && (self.min.y..=self.max.y).contains(&point.y)
+            && (self.min.z..=self.max.z).contains(&point.z)
+    }
+
+    pub fn center(&self) -> Point {
+        Point::new(
+            (self.min.x + self.max.x) / 2.0,
+            (self.min.y + self.max.y) / 2.0,
+            (self.min.z + self.max.z) / 2.0,
+        )
+    }
+
+    // Whether the line the ray travels along passes through the box at all, in either
+    // direction. Used to skip the exact intersection test for objects the ray can't hit.
+    pub fn intersects(&self, ray: Ray) -> bool {
+        let axes = [
+            (ray.origin.x, ray.direction.x, self.min.x, self.max.x),
+            (ray.origin.y, ray.direction.y, self.min.y, self.max.y),
+            (ray.origin.z, ray.direction.z, self.min.z, self.max.z),
+        ];
+        let mut t_min = f64::NEG_INFINITY;
+        let mut t_max = f64::INFINITY;
+        for (origin, direction, min, max) in axes {
+            if direction.abs() < EPSILON {
+                // Parallel to this slab, so the ray has to start between its sides
+                if origin < min - EPSILON || origin > max + EPSILON {
+                    return false;
+                }
+                continue;
+            }
+            let t0 = (min - origin) / direction;
+            let t1 = (max - origin) / direction;
+            t_min = t_min.max(t0.min(t1));
+            t_max = t_max.min(t0.max(t1));
+        }
+        t_min <= t_max + EPSILON
+    }
+
+    // Transforms all eight corners and fits a new axis-aligned box around them
+    pub fn transform(&self, transformation: Matrix4) -> Bounds {
+        let corners = [
+            Point::new(self.min.x, self.min.y, self.min.z),
+            Point::new(self.min.x, self.min.y, self.max.z),
+            Point::new(self.min.x, self.max.y, self.min.z),
+            Point::new(self.min.x, self.max.y, self.max.z),
+            Point::new(self.max.x, self.min.y, self.min.z),
+            Point::new(self.max.x, self.min.y, self.max.z),
+            Point::new(self.max.x, self.max.y, self.min.z),
+            Point::new(self.max.x, self.max.y, self.max.z),
+        ];
+        let mut bounds = Bounds::empty();
+        for corner in corners {
+            bounds.add_point(transformation * corner);
+        }
+        bounds
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::bounds::Bounds;
+    use crate::matrices::Matrix4;
+    use crate::rays::Ray;
+    use crate::tuples::{Point, Tuple, Vector};
+    use std::f64::consts::{PI, SQRT_2};
+
+    #[test]
+    fn adding_points_to_an_empty_bounding_box() {
+        let mut bounds = Bounds::empty();
+        bounds.add_point(Point::new(-5.0, 2.0, 0.0));
+        bounds.add_point(Point::new(7.0, 0.0, -3.0));
+        assert_eq!(bounds.min, Point::new(-5.0, 0.0, -3.0));
+        assert_eq!(bounds.max, Point::new(7.0, 2.0, 0.0));
+    }
+
+    #[test]
+    fn merging_two_bounding_boxes() {
+        let first = Bounds::new(Point::new(-5.0, -2.0, 0.0), Point::new(7.0, 4.0, 4.0));
+        let second = Bounds::new(Point::new(8.0, -7.0, -2.0), Point::new(14.0, 2.0, 8.0));
+        let merged = first.merge(&second);
+        assert_eq!(merged.min, Point::new(-5.0, -7.0, -2.0));
+        assert_eq!(merged.max, Point::new(14.0, 4.0, 8.0));
+    }
+
+    #[test]
+    fn checking_whether_a_box_contains_a_point() {
+        let bounds = Bounds::new(Point::new(5.0, -2.0, 0.0), Point::new(11.0, 4.0, 7.0));
+        assert!(bounds.contains_point(Point::new(5.0, -2.0, 0.0)));
+        assert!(bounds.contains_point(Point::new(8.0, 1.0, 3.0)));
+        assert!(!bounds.contains_point(Point::new(3.0, 0.0, 3.0)));
+        assert!(!bounds.contains_point(Point::new(8.0, 1.0, 8.0)));
+    }
+
+    #[test]
+    fn rays_that_cross_a_box() {
+        let bounds = Bounds::new(Point::new(-1.0, -1.0, -1.0), Point::new(1.0, 1.0, 1.0));
+        let cases = [
+            (Point::new(5.0, 0.5, 0.0), Vector::new(-1.0, 0.0, 0.0)),
+            (Point::new(-5.0, 0.5, 0.0), Vector::new(1.0, 0.0, 0.0)),
+            (Point::new(0.5, 5.0, 0.0), Vector::new(0.0, -1.0, 0.0)),
+            (Point::new(0.0, 0.5, 0.0), Vector::new(0.0, 0.0, 1.0)),
+            (Point::new(-2.0, -2.0, -2.0), Vector::new(1.0, 1.0, 1.0)),
+            // Lines through the box count even when the box is behind the ray
+            (Point::new(0.0, 0.0, 5.0), Vector::new(0.0, 0.0, 1.0)),
+        ];
+        for (origin, direction) in cases {
+            assert!(bounds.intersects(Ray::new(origin, direction)));
+        }
+    }
+
+    #[test]
+    fn rays_that_miss_a_box() {
+        let bounds = Bounds::new(Point::new(-1.0, -1.0, -1.0), Point::new(1.0, 1.0, 1.0));
+        let cases = [
+            (Point::new(-2.0, 0.0, 0.0), Vector::new(2.0, 4.0, 6.0)),
+            (Point::new(0.0, -2.0, 0.0), Vector::new(6.0, 2.0, 4.0)),
+            (Point::new(2.0, 0.0, 2.0), Vector::new(0.0, 0.0, -1.0)),
+            (Point::new(0.0, 2.0, 2.0), Vector::new(0.0, -1.0, 0.0)),
+        ];
+        for (origin, direction) in cases {
+            assert!(!bounds.intersects(Ray::new(origin, direction)));
+        }
+    }
+
+    #[test]
+    fn transforming_a_bounding_box() {
+        let bounds = Bounds::new(Point::new(-1.0, -1.0, -1.0), Point::new(1.0, 1.0, 1.0));
+        let transformation = Matrix4::rotate_x(PI / 4.0) * Matrix4::rotate_y(PI / 4.0);
+        let transformed = bounds.transform(transformation);
+        assert_eq!(transformed.min, Point::new(-SQRT_2, -1.70710, -1.70710));
+        assert_eq!(transformed.max, Point::new(SQRT_2, 1.70710, 1.70710));
+    }
+}
@@ -1,18 +1,50 @@
 use crate::canvas::Canvas;
-use crate::matrices::Matrix4;
+use crate::color::Color;
+use crate::matrices::{Matrix4, MatrixError};
 use crate::rays::Ray;
-use crate::tuples::{Point, Tuple};
-use crate::world::World;
+use crate::rng::Rng;
+use crate::stats::RenderStats;
+use crate::transformations::{view_transform, Transform};
+use crate::tuples::{Point, Tuple, Vector};
+use crate::world::{SceneWarning, World};
+use std::f64::consts::PI;
+
+// Number of lens positions sampled per ray when depth of field is enabled
+const DEPTH_OF_FIELD_SAMPLES: u32 = 16;
+// Number of moments sampled per ray while the shutter is open
+const MOTION_BLUR_SAMPLES: u32 = 8;
+
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum Projection {
+    Perspective,
+    // Parallel rays spread over a width x height view plane, in world units
+    Orthographic { width: f64, height: f64 },
+}
 
 #[derive(Debug, Copy, Clone)]
 pub struct Camera {
     pub hsize: u32,
     pub vsize: u32,
     pub field_of_view: f64,
-    pub transform: Matrix4,
+    transform: Transform,
     pub pixel_size: f64,
     pub half_width: f64,
     pub half_height: f64,
+    // Each pixel is split into an antialias_samples x antialias_samples grid of rays
+    pub antialias_samples: u32,
+    // An aperture of 0.0 is a pinhole camera, where everything is in focus
+    pub aperture: f64,
+    pub focal_distance: f64,
+    // Number of straight edges on the aperture, which gives out-of-focus highlights that
+    // shape. Anything below 3 is a perfectly round aperture.
+    pub aperture_blades: u32,
+    pub projection: Projection,
+    // How much of the frame the shutter stays open for, from 0.0 (no motion blur) to 1.0
+    pub shutter: f64,
+    // None samples the middle of each antialiasing cell. With a seed, samples are jittered
+    // randomly within their cell, the same way every time for the same seed. The seed also
+    // picks the samples on jittered area lights.
+    pub seed: Option<u64>,
 }
 
 impl Camera {
@@ -29,51 +61,512 @@ impl Camera {
             hsize,
             vsize,
             field_of_view,
-            transform: Matrix4::identity(),
+            transform: Transform::default(),
             pixel_size: (half_width * 2.0) / hsize as f64,
             half_width,
             half_height,
+            antialias_samples: 1,
+            aperture: 0.0,
+            focal_distance: 1.0,
+            aperture_blades: 0,
+            projection: Projection::Perspective,
+            shutter: 0.0,
+            seed: None,
+        }
+    }
+
+    pub fn transform(&self) -> Matrix4 {
+        self.transform.matrix()
+    }
+
+    // Also saves the inverse, so rays don't invert the matrix every time
+    pub fn set_transform(&mut self, transform: Matrix4) {
+        self.transform = Transform::new(transform);
+    }
+
+    // Like set_transform, but leaves the camera as it was if the transform can't be inverted
+    pub fn try_set_transform(&mut self, transform: Matrix4) -> Result<(), MatrixError> {
+        let transform = Transform::new(transform);
+        transform.try_inverse()?;
+        self.transform = transform;
+        Ok(())
+    }
+
+    // Checks for camera settings that would render nothing useful. See also World::validate.
+    pub fn validate(&self) -> Vec<SceneWarning> {
+        let mut warnings = Vec::new();
+        if self.transform.is_degenerate() {
+            warnings.push(SceneWarning::DegenerateCamera);
         }
+        // Orthographic cameras don't use the field of view
+        if self.projection == Projection::Perspective && self.field_of_view >= PI {
+            warnings.push(SceneWarning::WideFieldOfView(self.field_of_view));
+        }
+        warnings
+    }
+
+    pub fn builder() -> CameraBuilder {
+        CameraBuilder::new()
+    }
+
+    pub fn with_antialiasing(mut self, samples: u32) -> Camera {
+        self.antialias_samples = samples.max(1);
+        self
+    }
+
+    pub fn with_depth_of_field(mut self, aperture: f64, focal_distance: f64) -> Camera {
+        self.aperture = aperture.max(0.0);
+        self.focal_distance = focal_distance;
+        self
+    }
+
+    pub fn with_aperture_blades(mut self, blades: u32) -> Camera {
+        self.aperture_blades = blades;
+        self
+    }
+
+    pub fn with_projection(mut self, projection: Projection) -> Camera {
+        self.projection = projection;
+        self
+    }
+
+    pub fn with_shutter(mut self, shutter: f64) -> Camera {
+        self.shutter = shutter.clamp(0.0, 1.0);
+        self
+    }
+
+    pub fn with_seed(mut self, seed: u64) -> Camera {
+        self.seed = Some(seed);
+        self
     }
 
     pub fn ray_for_pixel(self, px: usize, py: usize) -> Ray {
-        let x_offset = (px as f64 + 0.5) * self.pixel_size;
-        let y_offset = (py as f64 + 0.5) * self.pixel_size;
+        self.ray_for_pixel_offset(px, py, 0.5, 0.5)
+    }
+
+    // The ray through the center of every pixel, row by row, for running a custom shading
+    // loop over the camera's view
+    pub fn rays(&self) -> impl Iterator<Item = (u32, u32, Ray)> {
+        let camera = *self;
+        (0..camera.vsize).flat_map(move |y| {
+            (0..camera.hsize).map(move |x| (x, y, camera.ray_for_pixel(x as usize, y as usize)))
+        })
+    }
+
+    // dx and dy are where in the pixel the ray passes through, from 0.0 to 1.0
+    pub fn ray_for_pixel_offset(self, px: usize, py: usize, dx: f64, dy: f64) -> Ray {
+        if let Projection::Orthographic { width, height } = self.projection {
+            let world_x = width / 2.0 - (px as f64 + dx) * width / self.hsize as f64;
+            let world_y = height / 2.0 - (py as f64 + dy) * height / self.vsize as f64;
+
+            let inverse = self.transform.inverse();
+            let origin = inverse * Point::new(world_x, world_y, 0.0);
+            let direction = (inverse * Vector::new(0.0, 0.0, -1.0)).normalize();
+            return Ray::new(origin, direction);
+        }
+
+        let x_offset = (px as f64 + dx) * self.pixel_size;
+        let y_offset = (py as f64 + dy) * self.pixel_size;
 
         let world_x = self.half_width - x_offset;
         let world_y = self.half_height - y_offset;
 
-        let pixel = self.transform.inverse().unwrap() * Point::new(world_x, world_y, -1.0);
-        let origin = self.transform.inverse().unwrap() * Point::new(0.0, 0.0, 0.0);
+        let inverse = self.transform.inverse();
+        let pixel = inverse * Point::new(world_x, world_y, -1.0);
+        let origin = inverse * Point::new(0.0, 0.0, 0.0);
         let direction = (pixel - origin).normalize();
         Ray::new(origin, direction)
     }
 
+    // Starts the ray from (lens_x, lens_y) on the camera plane and aims it at the point where
+    // the pinhole ray crosses the focal plane, so only things at focal_distance stay sharp
+    pub fn ray_through_lens(
+        self,
+        px: usize,
+        py: usize,
+        dx: f64,
+        dy: f64,
+        lens_x: f64,
+        lens_y: f64,
+    ) -> Ray {
+        let pinhole_ray = self.ray_for_pixel_offset(px, py, dx, dy);
+        if self.aperture <= 0.0 {
+            return pinhole_ray;
+        }
+
+        let focal_point = pinhole_ray.position(self.focal_distance);
+        let lens_offset = self.transform.inverse() * Vector::new(lens_x, lens_y, 0.0);
+        let origin = pinhole_ray.origin + lens_offset;
+        Ray::new(origin, (focal_point - origin).normalize())
+    }
+
+    // Spreads samples evenly over the aperture disk using a golden-angle spiral, then pulls
+    // them in to fit the aperture's polygon when it has blades
+    fn lens_sample(&self, sample: u32, samples: u32) -> (f64, f64) {
+        let golden_angle = PI * (3.0 - 5.0_f64.sqrt());
+        let theta = sample as f64 * golden_angle;
+        let radius = self.aperture / 2.0
+            * ((sample as f64 + 0.5) / samples as f64).sqrt()
+            * self.aperture_edge(theta);
+        (radius * theta.cos(), radius * theta.sin())
+    }
+
+    // How far the edge of the aperture is at angle theta, as a fraction of its full radius.
+    // A polygon's edge is closest to the center halfway between two corners.
+    fn aperture_edge(&self, theta: f64) -> f64 {
+        if self.aperture_blades < 3 {
+            return 1.0;
+        }
+        let segment = 2.0 * PI / self.aperture_blades as f64;
+        let offset = theta.rem_euclid(segment) - segment / 2.0;
+        (segment / 2.0).cos() / offset.cos()
+    }
+
     pub fn render(&self, world: World) -> Canvas {
+        self.render_with_progress(&world, |_, _| {})
+    }
+
+    // on_row is called with (rows_done, total_rows) after each row is finished
+    pub fn render_with_progress<F: FnMut(u32, u32)>(&self, world: &World, mut on_row: F) -> Canvas {
         let mut image = Canvas::new(self.hsize, self.vsize);
         for y in 0..self.vsize {
             for x in 0..self.hsize {
-                // This clone makes me sad.
-                // I think it would be fixed if we just made matrices no bigger than 4x4.
-                let ray = self.ray_for_pixel(x as usize, y as usize);
-                let color = world.color_at(ray);
+                let color = self.color_for_pixel(world, x as usize, y as usize);
                 image.write_pixel(x, y, &color);
             }
+            on_row(y + 1, self.vsize);
+        }
+
+        image
+    }
+
+    // Renders the objects along with a mask of how much of each pixel they cover, from black
+    // (only background) to white (fully covered). Pass the mask to Canvas::to_rgba_image to
+    // cut the objects out of their background. Each pixel only averages the samples that hit
+    // an object, so edges don't carry a fringe of background color, and pixels no object
+    // covers are black.
+    pub fn render_with_coverage(&self, world: &World) -> (Canvas, Canvas) {
+        let mut image = Canvas::new(self.hsize, self.vsize);
+        let mut mask = Canvas::new(self.hsize, self.vsize);
+        for y in 0..self.vsize {
+            for x in 0..self.hsize {
+                let (_, object_color, coverage) = self.sample_pixel(world, x as usize, y as usize);
+                image.write_pixel(x, y, &object_color);
+                mask.write_pixel(x, y, &(Color::white() * coverage));
+            }
+        }
+        (image, mask)
+    }
+
+    // Renders the whole image, counting the work done along the way. Resets the world's
+    // counters first.
+    pub fn render_with_stats(&self, world: &World) -> (Canvas, RenderStats) {
+        world.reset_stats();
+        let image = self.render_region(world, 0, 0, self.hsize, self.vsize);
+        (image, world.stats())
+    }
+
+    // Where a world point lands on the image, in fractional pixels, so that pixel (px, py)
+    // covers px..px + 1 and py..py + 1. Returns None for points behind the camera.
+    pub fn project(&self, point: Point) -> Option<(f64, f64)> {
+        let camera_point = self.transform.matrix() * point;
+        if camera_point.z >= 0.0 {
+            return None;
+        }
+
+        if let Projection::Orthographic { width, height } = self.projection {
+            let px = (width / 2.0 - camera_point.x) * self.hsize as f64 / width;
+            let py = (height / 2.0 - camera_point.y) * self.vsize as f64 / height;
+            return Some((px, py));
+        }
+
+        // Scale onto the canvas plane at z = -1, then reverse ray_for_pixel's offsets
+        let world_x = camera_point.x / -camera_point.z;
+        let world_y = camera_point.y / -camera_point.z;
+        Some((
+            (self.half_width - world_x) / self.pixel_size,
+            (self.half_height - world_y) / self.pixel_size,
+        ))
+    }
+
+    // Debug view that draws the edges of every object's world-space bounding box on black
+    pub fn render_bounds(&self, world: &World) -> Canvas {
+        let palette = [
+            Color::white(),
+            Color::red(),
+            Color::green(),
+            Color::blue(),
+            Color::new(1.0, 1.0, 0.0),
+            Color::new(0.0, 1.0, 1.0),
+            Color::new(1.0, 0.0, 1.0),
+        ];
+        let mut image = Canvas::new(self.hsize, self.vsize);
+        for (index, object) in world.objects.iter().enumerate() {
+            let bounds = object.world_bounds();
+            let color = palette[index % palette.len()];
+            let corner = |i: usize| {
+                Point::new(
+                    if i & 1 == 0 {
+                        bounds.min.x
+                    } else {
+                        bounds.max.x
+                    },
+                    if i & 2 == 0 {
+                        bounds.min.y
+                    } else {
+                        bounds.max.y
+                    },
+                    if i & 4 == 0 {
+                        bounds.min.z
+                    } else {
+                        bounds.max.z
+                    },
+                )
+            };
+            // Each edge joins two corners that differ along a single axis
+            for i in 0..8 {
+                for axis in [1, 2, 4] {
+                    if i & axis != 0 {
+                        continue;
+                    }
+                    // Edges that cross behind the camera are skipped rather than clipped
+                    if let (Some((x0, y0)), Some((x1, y1))) =
+                        (self.project(corner(i)), self.project(corner(i | axis)))
+                    {
+                        image.draw_line(
+                            x0.floor() as i64,
+                            y0.floor() as i64,
+                            x1.floor() as i64,
+                            y1.floor() as i64,
+                            &color,
+                        );
+                    }
+                }
+            }
         }
 
         image
     }
+
+    // Index into world.objects of the object seen through the center of a pixel, if any
+    pub fn object_at(&self, world: &World, px: u32, py: u32) -> Option<usize> {
+        let ray = self.ray_for_pixel(px as usize, py as usize);
+        world.cast(ray).map(|hit| hit.index)
+    }
+
+    // Renders the pixels from (x0, y0) up to but not including (x1, y1) into a canvas just
+    // big enough to hold them. Paste it back into a full render with Canvas::blit(.., x0, y0).
+    pub fn render_region(&self, world: &World, x0: u32, y0: u32, x1: u32, y1: u32) -> Canvas {
+        let x1 = x1.min(self.hsize);
+        let y1 = y1.min(self.vsize);
+        let x0 = x0.min(x1);
+        let y0 = y0.min(y1);
+        let mut image = Canvas::new(x1 - x0, y1 - y0);
+        for y in y0..y1 {
+            for x in x0..x1 {
+                let color = self.color_for_pixel(world, x as usize, y as usize);
+                image.write_pixel(x - x0, y - y0, &color);
+            }
+        }
+
+        image
+    }
+
+    // Averages the colors of an evenly spaced grid of rays through the pixel, each one
+    // spread over the lens when depth of field is enabled, and over the time the shutter
+    // is open when motion blur is enabled
+    fn color_for_pixel(&self, world: &World, px: usize, py: usize) -> Color {
+        self.sample_pixel(world, px, py).0
+    }
+
+    // Averages every antialiasing, lens and time sample for the pixel. Also returns the
+    // average of just the samples that hit an object (black if none did), and the fraction
+    // of the samples that hit one, for the coverage mask.
+    fn sample_pixel(&self, world: &World, px: usize, py: usize) -> (Color, Color, f64) {
+        // A camera squashed flat by its transform has no rays to cast
+        if self.transform.is_degenerate() {
+            return (Color::black(), Color::black(), 0.0);
+        }
+        // The field can be set to 0 directly, which would average no samples at all
+        let samples = self.antialias_samples.max(1);
+        let lens_samples = if self.aperture > 0.0 {
+            DEPTH_OF_FIELD_SAMPLES
+        } else {
+            1
+        };
+        let time_samples = if self.shutter > 0.0 {
+            MOTION_BLUR_SAMPLES
+        } else {
+            1
+        };
+        let mut rng = self
+            .seed
+            .map(|seed| Rng::for_pixel(px as u32, py as u32, seed));
+        let mut sum = Color::black();
+        let mut hit_sum = Color::black();
+        let mut hits = 0;
+        for sy in 0..samples {
+            for sx in 0..samples {
+                let (jitter_x, jitter_y) = match rng.as_mut() {
+                    Some(rng) => (rng.next_f64(), rng.next_f64()),
+                    None => (0.5, 0.5),
+                };
+                let dx = (sx as f64 + jitter_x) / samples as f64;
+                let dy = (sy as f64 + jitter_y) / samples as f64;
+                for lens in 0..lens_samples {
+                    let (lens_x, lens_y) = self.lens_sample(lens, lens_samples);
+                    let mut ray = self.ray_through_lens(px, py, dx, dy, lens_x, lens_y);
+                    for time in 0..time_samples {
+                        ray.time = self.time_sample(time, time_samples);
+                        let (color, hit) = world.color_at_with_hit(ray, rng.as_mut());
+                        sum = sum + color;
+                        if hit {
+                            hit_sum = hit_sum + color;
+                            hits += 1;
+                        }
+                    }
+                }
+            }
+        }
+        let count = (samples * samples * lens_samples * time_samples) as f64;
+        let object_color = if hits > 0 {
+            hit_sum * (1.0 / hits as f64)
+        } else {
+            Color::black()
+        };
+        (sum * (1.0 / count), object_color, hits as f64 / count)
+    }
+
+    // Spreads samples evenly over the time the shutter is open
+    fn time_sample(&self, sample: u32, samples: u32) -> f64 {
+        if self.shutter <= 0.0 {
+            return 0.0;
+        }
+        self.shutter * (sample as f64 + 0.5) / samples as f64
+    }
+
+    // Renders rows concurrently, then hands the finished rows to the canvas in one go
+    #[cfg(feature = "parallel")]
+    pub fn render_parallel(&self, world: &World) -> Canvas {
+        use rayon::prelude::*;
+
+        let pixels: Vec<Vec<Color>> = (0..self.vsize)
+            .into_par_iter()
+            .map(|y| {
+                (0..self.hsize)
+                    .map(|x| self.color_for_pixel(world, x as usize, y as usize))
+                    .collect()
+            })
+            .collect();
+
+        let mut image = Canvas::new(self.hsize, self.vsize);
+        image.pixels = pixels;
+        image
+    }
+}
+
+// Configures a camera in one chain, instead of building it and then setting its transform.
+// Settings are applied in build, so they can be given in any order.
+#[derive(Debug, Copy, Clone)]
+pub struct CameraBuilder {
+    hsize: u32,
+    vsize: u32,
+    field_of_view: f64,
+    transform: Matrix4,
+    antialias_samples: u32,
+    aperture: f64,
+    focal_distance: f64,
+    projection: Projection,
+    seed: Option<u64>,
+}
+
+impl CameraBuilder {
+    pub fn new() -> CameraBuilder {
+        CameraBuilder {
+            hsize: 100,
+            vsize: 100,
+            field_of_view: PI / 3.0,
+            transform: Matrix4::identity(),
+            antialias_samples: 1,
+            aperture: 0.0,
+            focal_distance: 1.0,
+            projection: Projection::Perspective,
+            seed: None,
+        }
+    }
+
+    pub fn resolution(mut self, hsize: u32, vsize: u32) -> CameraBuilder {
+        self.hsize = hsize;
+        self.vsize = vsize;
+        self
+    }
+
+    // In radians, like Camera::new
+    pub fn fov(mut self, field_of_view: f64) -> CameraBuilder {
+        self.field_of_view = field_of_view;
+        self
+    }
+
+    pub fn transform(mut self, transform: Matrix4) -> CameraBuilder {
+        self.transform = transform;
+        self
+    }
+
+    pub fn look_at(self, from: Point, to: Point, up: Vector) -> CameraBuilder {
+        self.transform(view_transform(from, to, up))
+    }
+
+    pub fn antialiasing(mut self, samples: u32) -> CameraBuilder {
+        self.antialias_samples = samples;
+        self
+    }
+
+    pub fn depth_of_field(mut self, aperture: f64, focal_distance: f64) -> CameraBuilder {
+        self.aperture = aperture;
+        self.focal_distance = focal_distance;
+        self
+    }
+
+    pub fn projection(mut self, projection: Projection) -> CameraBuilder {
+        self.projection = projection;
+        self
+    }
+
+    pub fn seed(mut self, seed: u64) -> CameraBuilder {
+        self.seed = Some(seed);
+        self
+    }
+
+    pub fn build(self) -> Camera {
+        let mut camera = Camera::new(self.hsize, self.vsize, self.field_of_view)
+            .with_antialiasing(self.antialias_samples)
+            .with_depth_of_field(self.aperture, self.focal_distance)
+            .with_projection(self.projection);
+        camera.set_transform(self.transform);
+        camera.seed = self.seed;
+        camera
+    }
+}
+
+impl Default for CameraBuilder {
+    fn default() -> CameraBuilder {
+        CameraBuilder::new()
+    }
 }
 
 #[cfg(test)]
 mod tests {
-    use crate::camera::Camera;
+    use crate::camera::{Camera, CameraBuilder, Projection};
+    use crate::canvas::Canvas;
     use crate::color::Color;
     use crate::floats::float_equal;
-    use crate::matrices::Matrix4;
+    use crate::materials::Material;
+    use crate::matrices::{Matrix4, MatrixError};
+    use crate::objects::{Intersectable, Object};
+    use crate::sphere::Sphere;
     use crate::transformations::view_transform;
     use crate::tuples::{Point, Tuple, Vector};
-    use crate::world::World;
+    use crate::world::{Background, SceneWarning, World};
     use std::f64::consts::PI;
 
     #[test]
@@ -85,7 +578,74 @@ mod tests {
         assert_eq!(camera.hsize, 160);
         assert_eq!(camera.vsize, 120);
         assert_eq!(camera.field_of_view, PI / 2.0);
-        assert_eq!(camera.transform, Matrix4::identity());
+        assert_eq!(camera.transform(), Matrix4::identity());
+    }
+
+    #[test]
+    fn setting_the_camera_transform_caches_its_inverse() {
+        let mut camera = Camera::new(160, 120, PI / 2.0);
+        camera.set_transform(Matrix4::translate(0.0, -2.0, 5.0));
+        assert_eq!(camera.transform(), Matrix4::translate(0.0, -2.0, 5.0));
+        assert_eq!(
+            camera.transform.inverse(),
+            Matrix4::translate(0.0, 2.0, -5.0)
+        );
+        assert_eq!(
+            camera.ray_for_pixel(80, 60).origin,
+            Point::new(0.0, 2.0, -5.0)
+        );
+    }
+
+    #[test]
+    fn a_camera_rejects_a_transform_it_cannot_invert() {
+        let mut camera = Camera::new(11, 11, PI / 2.0);
+        let flat = Matrix4::scale(1.0, 0.0, 1.0);
+        assert_eq!(
+            camera.try_set_transform(flat),
+            Err(MatrixError::NotInvertible)
+        );
+        assert_eq!(camera.transform(), Matrix4::identity());
+
+        // Set anyway, it renders black instead of panicking
+        camera.set_transform(flat);
+        let image = camera.render(World::default());
+        assert_eq!(image.pixel_at(5, 5), Color::black());
+    }
+
+    #[test]
+    fn validating_a_camera_with_mistakes() {
+        let mut camera = Camera::new(11, 11, PI / 2.0);
+        camera.set_transform(view_transform(
+            Point::new(0.0, 0.0, -5.0),
+            Point::new(0.0, 0.0, 0.0),
+            Vector::new(0.0, 1.0, 0.0),
+        ));
+        assert!(camera.validate().is_empty());
+
+        let mut camera = Camera::new(11, 11, PI);
+        let from = Point::new(1.0, 2.0, 3.0);
+        camera.set_transform(view_transform(from, from, Vector::new(0.0, 1.0, 0.0)));
+        assert_eq!(
+            camera.validate(),
+            vec![
+                SceneWarning::DegenerateCamera,
+                SceneWarning::WideFieldOfView(PI)
+            ]
+        );
+
+        // Looking straight along up is just as degenerate
+        camera.set_transform(view_transform(
+            Point::new(0.0, 0.0, 0.0),
+            Point::new(0.0, 5.0, 0.0),
+            Vector::new(0.0, 1.0, 0.0),
+        ));
+        assert!(camera.validate().contains(&SceneWarning::DegenerateCamera));
+
+        let camera = camera.with_projection(Projection::Orthographic {
+            width: 4.0,
+            height: 4.0,
+        });
+        assert_eq!(camera.validate(), vec![SceneWarning::DegenerateCamera]);
     }
 
     #[test]
@@ -122,7 +682,7 @@ mod tests {
     #[test]
     fn constructing_ray_when_camera_transformed() {
         let mut camera = Camera::new(201, 101, PI / 2.0);
-        camera.transform = Matrix4::rotate_y(PI / 4.0) * Matrix4::translate(0.0, -2.0, 5.0);
+        camera.set_transform(Matrix4::rotate_y(PI / 4.0) * Matrix4::translate(0.0, -2.0, 5.0));
         let ray = camera.ray_for_pixel(100, 50);
         assert_eq!(ray.origin, Point::new(0.0, 2.0, -5.0));
         assert_eq!(
@@ -138,9 +698,491 @@ mod tests {
         let from = Point::new(0.0, 0.0, -5.0);
         let to = Point::new(0.0, 0.0, 0.0);
         let up = Vector::new(0.0, 1.0, 0.0);
-        camera.transform = view_transform(from, to, up);
+        camera.set_transform(view_transform(from, to, up));
 
         let image = camera.render(world);
         assert_eq!(image.pixel_at(5, 5), Color::new(0.38066, 0.47583, 0.2855));
     }
+
+    #[cfg(feature = "parallel")]
+    #[test]
+    fn parallel_render_matches_serial_render() {
+        let mut camera = Camera::new(11, 11, PI / 2.0);
+        let from = Point::new(0.0, 0.0, -5.0);
+        let to = Point::new(0.0, 0.0, 0.0);
+        let up = Vector::new(0.0, 1.0, 0.0);
+        camera.set_transform(view_transform(from, to, up));
+
+        let parallel = camera.render_parallel(&World::default());
+        let serial = camera.render(World::default());
+        assert_eq!(parallel.pixels, serial.pixels);
+    }
+
+    #[test]
+    fn camera_defaults_to_one_sample_per_pixel() {
+        let camera = Camera::new(160, 120, PI / 2.0);
+        assert_eq!(camera.antialias_samples, 1);
+        assert_eq!(camera.with_antialiasing(4).antialias_samples, 4);
+    }
+
+    #[test]
+    fn zero_antialias_samples_still_renders_one_sample() {
+        let mut camera = Camera::new(11, 11, PI / 2.0);
+        camera.set_transform(view_transform(
+            Point::new(0.0, 0.0, -5.0),
+            Point::new(0.0, 0.0, 0.0),
+            Vector::new(0.0, 1.0, 0.0),
+        ));
+        let expected = camera.render(World::default());
+        camera.antialias_samples = 0;
+        assert_eq!(camera.render(World::default()), expected);
+    }
+
+    #[test]
+    fn offset_ray_through_pixel_corner() {
+        let camera = Camera::new(201, 101, PI / 2.0);
+        let corner = camera.ray_for_pixel_offset(100, 50, 0.0, 0.0);
+        let center = camera.ray_for_pixel_offset(100, 50, 0.5, 0.5);
+        assert_eq!(center.direction, camera.ray_for_pixel(100, 50).direction);
+        assert_eq!(
+            corner.direction,
+            Vector::new(1.0 / 201.0, 1.0 / 201.0, -1.0).normalize()
+        );
+    }
+
+    #[test]
+    fn antialiasing_averages_a_grid_of_rays() {
+        let world = World::default();
+        let mut camera = Camera::new(11, 11, PI / 2.0).with_antialiasing(2);
+        let from = Point::new(0.0, 0.0, -5.0);
+        let to = Point::new(0.0, 0.0, 0.0);
+        let up = Vector::new(0.0, 1.0, 0.0);
+        camera.set_transform(view_transform(from, to, up));
+
+        let mut expected = Color::black();
+        for (dx, dy) in [(0.25, 0.25), (0.75, 0.25), (0.25, 0.75), (0.75, 0.75)] {
+            expected = expected + world.color_at(camera.ray_for_pixel_offset(6, 5, dx, dy));
+        }
+        let image = camera.render(world);
+        assert_eq!(image.pixel_at(6, 5), expected * 0.25);
+    }
+
+    #[test]
+    fn camera_defaults_to_a_pinhole() {
+        let camera = Camera::new(160, 120, PI / 2.0);
+        assert_eq!(camera.aperture, 0.0);
+        let camera = camera.with_depth_of_field(0.5, 5.0);
+        assert_eq!(camera.aperture, 0.5);
+        assert_eq!(camera.focal_distance, 5.0);
+    }
+
+    #[test]
+    fn pinhole_camera_ignores_lens_offset() {
+        let camera = Camera::new(201, 101, PI / 2.0);
+        let ray = camera.ray_through_lens(100, 50, 0.5, 0.5, 0.3, 0.3);
+        assert_eq!(ray.origin, Point::new(0.0, 0.0, 0.0));
+        assert_eq!(ray.direction, Vector::new(0.0, 0.0, -1.0));
+    }
+
+    #[test]
+    fn lens_rays_converge_on_the_focal_plane() {
+        let mut camera = Camera::new(201, 101, PI / 2.0).with_depth_of_field(0.5, 5.0);
+        camera.set_transform(Matrix4::rotate_y(PI / 4.0) * Matrix4::translate(0.0, -2.0, 5.0));
+        let pinhole = camera.ray_for_pixel(20, 30);
+        let focal_point = pinhole.position(5.0);
+        for (lens_x, lens_y) in [(0.25, 0.0), (0.0, -0.25), (-0.1, 0.1)] {
+            let ray = camera.ray_through_lens(20, 30, 0.5, 0.5, lens_x, lens_y);
+            assert_ne!(ray.origin, pinhole.origin);
+            let to_focal_point = focal_point - ray.origin;
+            assert_eq!(ray.direction, to_focal_point.normalize());
+        }
+    }
+
+    #[test]
+    fn lens_samples_stay_inside_the_aperture() {
+        let camera = Camera::new(11, 11, PI / 2.0).with_depth_of_field(0.5, 5.0);
+        for sample in 0..16 {
+            let (x, y) = camera.lens_sample(sample, 16);
+            assert!((x * x + y * y).sqrt() <= 0.25);
+        }
+    }
+
+    #[test]
+    fn camera_defaults_to_perspective() {
+        let camera = Camera::new(160, 120, PI / 2.0);
+        assert_eq!(camera.projection, Projection::Perspective);
+    }
+
+    #[test]
+    fn orthographic_rays_are_parallel() {
+        let camera = Camera::new(201, 101, PI / 2.0).with_projection(Projection::Orthographic {
+            width: 4.0,
+            height: 2.0,
+        });
+        let center = camera.ray_for_pixel(100, 50);
+        let corner = camera.ray_for_pixel(0, 0);
+        assert_eq!(center.origin, Point::new(0.0, 0.0, 0.0));
+        assert_eq!(center.direction, Vector::new(0.0, 0.0, -1.0));
+        assert_eq!(corner.direction, Vector::new(0.0, 0.0, -1.0));
+        assert_eq!(
+            corner.origin,
+            Point::new(2.0 - 0.5 * 4.0 / 201.0, 1.0 - 0.5 * 2.0 / 101.0, 0.0)
+        );
+    }
+
+    #[test]
+    fn orthographic_rays_follow_camera_transform() {
+        let mut camera =
+            Camera::new(201, 101, PI / 2.0).with_projection(Projection::Orthographic {
+                width: 4.0,
+                height: 2.0,
+            });
+        camera.set_transform(Matrix4::rotate_y(PI / 4.0) * Matrix4::translate(0.0, -2.0, 5.0));
+        let ray = camera.ray_for_pixel(100, 50);
+        assert_eq!(ray.origin, Point::new(0.0, 2.0, -5.0));
+        assert_eq!(
+            ray.direction,
+            Vector::new(2.0_f64.sqrt() / 2.0, 0.0, -(2.0_f64.sqrt() / 2.0))
+        );
+    }
+
+    #[test]
+    fn render_reports_progress_after_each_row() {
+        let camera = Camera::new(4, 3, PI / 2.0);
+        let mut progress = Vec::new();
+        camera.render_with_progress(&World::default(), |done, total| {
+            progress.push((done, total))
+        });
+        assert_eq!(progress, vec![(1, 3), (2, 3), (3, 3)]);
+    }
+
+    #[test]
+    fn rendering_a_region_matches_the_full_render() {
+        let world = World::default();
+        let mut camera = Camera::new(11, 11, PI / 2.0);
+        camera.set_transform(view_transform(
+            Point::new(0.0, 0.0, -5.0),
+            Point::new(0.0, 0.0, 0.0),
+            Vector::new(0.0, 1.0, 0.0),
+        ));
+        let region = camera.render_region(&world, 4, 3, 7, 8);
+        assert_eq!(region.width, 3);
+        assert_eq!(region.height, 5);
+        assert_eq!(region.pixel_at(1, 2), Color::new(0.38066, 0.47583, 0.2855));
+
+        let mut image = Canvas::new(11, 11);
+        image.blit(&region, 4, 3);
+        let full = camera.render(world);
+        for y in 3..8 {
+            for x in 4..7 {
+                assert_eq!(image.pixel_at(x, y), full.pixel_at(x, y));
+            }
+        }
+        assert_eq!(image.pixel_at(0, 0), Color::black());
+    }
+
+    #[test]
+    fn render_region_is_clamped_to_the_image() {
+        let camera = Camera::new(4, 3, PI / 2.0);
+        let region = camera.render_region(&World::default(), 2, 1, 10, 10);
+        assert_eq!(region.width, 2);
+        assert_eq!(region.height, 2);
+        let empty = camera.render_region(&World::default(), 6, 5, 10, 10);
+        assert_eq!(empty.width, 0);
+        assert_eq!(empty.height, 0);
+    }
+
+    #[test]
+    fn picking_the_object_under_a_pixel() {
+        let world = World::default();
+        let mut camera = Camera::new(11, 11, PI / 2.0);
+        camera.set_transform(view_transform(
+            Point::new(0.0, 0.0, -5.0),
+            Point::new(0.0, 0.0, 0.0),
+            Vector::new(0.0, 1.0, 0.0),
+        ));
+        // The outer sphere hides the inner one from the front
+        assert_eq!(camera.object_at(&world, 5, 5), Some(0));
+        assert_eq!(camera.object_at(&world, 0, 0), None);
+
+        let world = World {
+            objects: vec![world.objects[1]],
+            ..Default::default()
+        };
+        assert_eq!(camera.object_at(&world, 5, 5), Some(0));
+        assert_eq!(camera.object_at(&world, 3, 5), None);
+    }
+
+    #[test]
+    fn projecting_points_reverses_ray_for_pixel() {
+        let mut camera = Camera::new(201, 101, PI / 2.0);
+        camera.set_transform(Matrix4::rotate_y(PI / 4.0) * Matrix4::translate(0.0, -2.0, 5.0));
+        let ray = camera.ray_for_pixel(30, 40);
+        let (px, py) = camera.project(ray.position(7.0)).unwrap();
+        assert!(float_equal(px, 30.5));
+        assert!(float_equal(py, 40.5));
+        // Behind the camera
+        assert_eq!(camera.project(ray.position(-1.0)), None);
+    }
+
+    #[test]
+    fn projecting_points_with_an_orthographic_camera() {
+        let camera = Camera::new(20, 10, PI / 2.0).with_projection(Projection::Orthographic {
+            width: 4.0,
+            height: 2.0,
+        });
+        let ray = camera.ray_for_pixel_offset(3, 7, 0.25, 0.75);
+        let (px, py) = camera.project(ray.position(3.0)).unwrap();
+        assert!(float_equal(px, 3.25));
+        assert!(float_equal(py, 7.75));
+    }
+
+    #[test]
+    fn rendering_bounding_boxes() {
+        let world = World {
+            objects: vec![Object::Sphere(Sphere::new())],
+            ..Default::default()
+        };
+        let mut camera = Camera::new(51, 51, PI / 3.0);
+        camera.set_transform(view_transform(
+            Point::new(0.0, 0.0, -5.0),
+            Point::new(0.0, 0.0, 0.0),
+            Vector::new(0.0, 1.0, 0.0),
+        ));
+        let image = camera.render_bounds(&world);
+        // The center of the box is empty, but its front edges are drawn
+        assert_eq!(image.pixel_at(25, 25), Color::black());
+        let (left, _) = camera.project(Point::new(1.0, 0.0, -1.0)).unwrap();
+        assert_eq!(image.pixel_at(left.floor() as u32, 25), Color::white());
+    }
+
+    #[test]
+    fn an_open_shutter_blurs_moving_objects() {
+        let mut sphere = Object::Sphere(Sphere::new());
+        sphere.set_material(Material {
+            emission: Color::white(),
+            ..Default::default()
+        });
+        sphere.set_motion(Matrix4::translate(3.0, 0.0, 0.0));
+        let world = World {
+            objects: vec![sphere],
+            lights: vec![],
+            ..Default::default()
+        };
+        let mut camera = Camera::new(11, 11, PI / 2.0);
+        camera.set_transform(view_transform(
+            Point::new(0.0, 0.0, -5.0),
+            Point::new(0.0, 0.0, 0.0),
+            Vector::new(0.0, 1.0, 0.0),
+        ));
+
+        let sharp = camera.color_for_pixel(&world, 5, 5);
+        let blurred = camera.with_shutter(1.0).color_for_pixel(&world, 5, 5);
+        // The sphere only covers the center for the first 3 of the 8 moments sampled
+        assert_eq!(sharp, Color::white());
+        assert_eq!(blurred, Color::new(0.375, 0.375, 0.375));
+    }
+
+    #[test]
+    fn rendering_with_stats_counts_the_work_done() {
+        let world = World::default();
+        let mut camera = Camera::new(11, 11, PI / 2.0);
+        camera.set_transform(view_transform(
+            Point::new(0.0, 0.0, -5.0),
+            Point::new(0.0, 0.0, 0.0),
+            Vector::new(0.0, 1.0, 0.0),
+        ));
+        let (image, stats) = camera.render_with_stats(&world);
+        assert_eq!(image.pixel_at(5, 5), Color::new(0.38066, 0.47583, 0.2855));
+        assert_eq!(stats.rays_cast, 121);
+        // Every camera ray and shadow ray is tested against both spheres
+        assert_eq!(
+            stats.intersection_tests,
+            2 * (stats.rays_cast + stats.shadow_rays)
+        );
+        // Only rays that hit something cast a shadow ray towards the single light
+        assert!(stats.shadow_rays > 0 && stats.shadow_rays < stats.rays_cast);
+    }
+
+    #[test]
+    fn seeded_renders_are_reproducible() {
+        let world = World::default();
+        let mut camera = Camera::new(11, 11, PI / 2.0).with_antialiasing(2);
+        camera.set_transform(view_transform(
+            Point::new(0.0, 0.0, -5.0),
+            Point::new(0.0, 0.0, 0.0),
+            Vector::new(0.0, 1.0, 0.0),
+        ));
+        let render = |seed| {
+            camera
+                .with_seed(seed)
+                .render_region(&world, 0, 0, 11, 11)
+                .to_ppm_string()
+        };
+        assert_eq!(render(1), render(1));
+        assert_ne!(render(1), render(2));
+    }
+
+    #[test]
+    fn bladed_apertures_are_polygons() {
+        let camera = Camera::new(10, 10, PI / 2.0).with_depth_of_field(2.0, 5.0);
+        assert_eq!(camera.aperture_edge(0.3), 1.0);
+
+        let hexagon = camera.with_aperture_blades(6);
+        // Corners reach the full radius, the middles of the edges don't
+        assert!(float_equal(hexagon.aperture_edge(0.0), 1.0));
+        assert!(float_equal(hexagon.aperture_edge(PI / 3.0), 1.0));
+        assert!(float_equal(
+            hexagon.aperture_edge(PI / 6.0),
+            (PI / 6.0).cos()
+        ));
+
+        // Every sample lies inside the hexagon. The aperture radius is 1.0 here, so the
+        // fraction from aperture_edge is also the distance to the edge.
+        for sample in 0..64 {
+            let (x, y) = hexagon.lens_sample(sample, 64);
+            let theta = y.atan2(x);
+            let radius = (x * x + y * y).sqrt();
+            assert!(radius <= hexagon.aperture_edge(theta) + 1e-9);
+        }
+    }
+
+    #[test]
+    fn building_a_camera_matches_configuring_one_afterwards() {
+        let from = Point::new(0.0, 0.0, -5.0);
+        let to = Point::new(0.0, 0.0, 0.0);
+        let up = Vector::new(0.0, 1.0, 0.0);
+        let built = Camera::builder()
+            .look_at(from, to, up)
+            .antialiasing(2)
+            .resolution(200, 125)
+            .fov(PI / 2.0)
+            .depth_of_field(0.1, 5.0)
+            .seed(7)
+            .build();
+
+        let mut expected = Camera::new(200, 125, PI / 2.0)
+            .with_antialiasing(2)
+            .with_depth_of_field(0.1, 5.0)
+            .with_seed(7);
+        expected.set_transform(view_transform(from, to, up));
+
+        assert_eq!(built.hsize, expected.hsize);
+        assert_eq!(built.vsize, expected.vsize);
+        assert!(float_equal(built.pixel_size, expected.pixel_size));
+        assert_eq!(built.transform(), expected.transform());
+        assert_eq!(built.antialias_samples, 2);
+        assert_eq!(built.aperture, 0.1);
+        assert_eq!(built.focal_distance, 5.0);
+        assert_eq!(built.seed, Some(7));
+        let (ray, expected_ray) = (
+            built.ray_for_pixel(100, 50),
+            expected.ray_for_pixel(100, 50),
+        );
+        assert_eq!(ray.origin, expected_ray.origin);
+        assert_eq!(ray.direction, expected_ray.direction);
+    }
+
+    #[test]
+    fn camera_builder_defaults_to_a_plain_camera() {
+        let camera = CameraBuilder::default().build();
+        assert_eq!(camera.transform(), Matrix4::identity());
+        assert_eq!(camera.antialias_samples, 1);
+        assert_eq!(camera.aperture, 0.0);
+        assert_eq!(camera.projection, Projection::Perspective);
+        assert_eq!(camera.seed, None);
+    }
+
+    #[test]
+    fn iterating_over_every_pixel_ray() {
+        let mut camera = Camera::new(4, 3, PI / 2.0);
+        camera.set_transform(view_transform(
+            Point::new(0.0, 0.0, -5.0),
+            Point::new(0.0, 0.0, 0.0),
+            Vector::new(0.0, 1.0, 0.0),
+        ));
+        let rays: Vec<_> = camera.rays().collect();
+        assert_eq!(rays.len(), 12);
+        assert_eq!((rays[0].0, rays[0].1), (0, 0));
+        assert_eq!((rays[5].0, rays[5].1), (1, 1));
+        for (x, y, ray) in rays {
+            let expected = camera.ray_for_pixel(x as usize, y as usize);
+            assert_eq!(ray.origin, expected.origin);
+            assert_eq!(ray.direction, expected.direction);
+        }
+    }
+
+    #[test]
+    fn coverage_is_white_where_rays_hit_geometry() {
+        let world = World::default();
+        let mut camera = Camera::new(11, 11, PI / 2.0);
+        camera.set_transform(view_transform(
+            Point::new(0.0, 0.0, -5.0),
+            Point::new(0.0, 0.0, 0.0),
+            Vector::new(0.0, 1.0, 0.0),
+        ));
+        let (image, mask) = camera.render_with_coverage(&world);
+        assert_eq!(
+            image.pixel_at(5, 5),
+            camera.render(World::default()).pixel_at(5, 5)
+        );
+        assert_eq!(image.pixel_at(0, 0), Color::black());
+        assert_eq!(mask.pixel_at(5, 5), Color::white());
+        assert_eq!(mask.pixel_at(0, 0), Color::black());
+    }
+
+    #[test]
+    fn antialiased_coverage_is_partial_along_edges() {
+        let world = World::default();
+        let mut camera = Camera::new(11, 11, PI / 2.0).with_antialiasing(4);
+        camera.set_transform(view_transform(
+            Point::new(0.0, 0.0, -5.0),
+            Point::new(0.0, 0.0, 0.0),
+            Vector::new(0.0, 1.0, 0.0),
+        ));
+        let (_, mask) = camera.render_with_coverage(&world);
+        let partial = mask
+            .pixels
+            .iter()
+            .flatten()
+            .any(|c| c.red > 0.0 && c.red < 1.0);
+        assert!(partial);
+    }
+
+    #[test]
+    fn coverage_renders_leave_the_background_out_of_edge_pixels() {
+        let mut camera = Camera::new(11, 11, PI / 2.0).with_antialiasing(4);
+        camera.set_transform(view_transform(
+            Point::new(0.0, 0.0, -5.0),
+            Point::new(0.0, 0.0, 0.0),
+            Vector::new(0.0, 1.0, 0.0),
+        ));
+        let with_background = |color| World {
+            background: Background::Solid(color),
+            ..Default::default()
+        };
+        let red = with_background(Color::new(1.0, 0.0, 0.0));
+        let blue = with_background(Color::new(0.0, 0.0, 1.0));
+        let (red_image, mask) = camera.render_with_coverage(&red);
+        let (blue_image, _) = camera.render_with_coverage(&blue);
+
+        let (x, y) = (0..11)
+            .flat_map(|y| (0..11).map(move |x| (x, y)))
+            .find(|&(x, y)| {
+                let coverage = mask.pixel_at(x, y).red;
+                coverage > 0.0 && coverage < 1.0
+            })
+            .unwrap();
+        // A plain render mixes the background into the edge, the cutout doesn't
+        assert_ne!(
+            camera
+                .render_region(&red, x, y, x + 1, y + 1)
+                .pixel_at(0, 0),
+            camera
+                .render_region(&blue, x, y, x + 1, y + 1)
+                .pixel_at(0, 0)
+        );
+        assert_eq!(red_image.pixel_at(x, y), blue_image.pixel_at(x, y));
+        assert_ne!(red_image.pixel_at(x, y), Color::black());
+    }
 }
@@ -1,17 +1,72 @@
 use crate::color::Color;
+use image::error::{
+    ImageFormatHint, ParameterError, ParameterErrorKind, UnsupportedError, UnsupportedErrorKind,
+};
+use image::{ImageError, ImageOutputFormat, Rgb, RgbImage, Rgba, RgbaImage};
+use std::fmt;
 use std::fs::File;
 use std::io::Write;
 use std::path::Path;
-use image::{ImageError, ImageOutputFormat, Rgb, RgbImage};
 
+#[derive(Debug, Clone, PartialEq)]
 pub struct Canvas {
     pub width: u32,
     pub height: u32,
     pub pixels: Vec<Vec<Color>>,
 }
 
+#[derive(Debug)]
+pub enum PpmError {
+    Io(std::io::Error),
+    InvalidMagicNumber(String),
+    InvalidHeader(String),
+    InvalidPixel(String),
+}
+
+impl fmt::Display for PpmError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            PpmError::Io(e) => write!(f, "Could not read PPM file: {}", e),
+            PpmError::InvalidMagicNumber(magic) => {
+                write!(f, "Expected PPM magic number P3, found '{}'", magic)
+            }
+            PpmError::InvalidHeader(message) => write!(f, "Invalid PPM header: {}", message),
+            PpmError::InvalidPixel(message) => write!(f, "Invalid PPM pixel data: {}", message),
+        }
+    }
+}
+
+impl std::error::Error for PpmError {}
+
+#[derive(Debug, PartialEq)]
+pub enum CanvasError {
+    // (width, height) of the canvas and of the one it was combined with
+    SizeMismatch((u32, u32), (u32, u32)),
+}
+
+impl fmt::Display for CanvasError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            CanvasError::SizeMismatch((w1, h1), (w2, h2)) => {
+                write!(f, "Canvas sizes differ: {}x{} and {}x{}", w1, h1, w2, h2)
+            }
+        }
+    }
+}
+
+impl std::error::Error for CanvasError {}
+
+impl From<std::io::Error> for PpmError {
+    fn from(e: std::io::Error) -> Self {
+        PpmError::Io(e)
+    }
+}
+
 const MAX_COLOR_VALUE: u32 = 255;
 const MAX_LINE_LENGTH: u32 = 70;
+// Used for PNG and JPEG output so images look right on sRGB displays. PPM output stays
+// linear to match the book.
+pub const DEFAULT_GAMMA: f64 = 2.2;
 
 impl Canvas {
     pub fn new(width: u32, height: u32) -> Canvas {
@@ -23,6 +78,81 @@ impl Canvas {
         }
     }
 
+    pub fn from_ppm_file<P: AsRef<Path>>(path: P) -> Result<Canvas, PpmError> {
+        let contents = std::fs::read_to_string(path)?;
+        Canvas::from_ppm_string(&contents)
+    }
+
+    // Parses a plain (P3) PPM. Comments and line breaks may appear anywhere between values.
+    pub fn from_ppm_string(s: &str) -> Result<Canvas, PpmError> {
+        let mut tokens = s
+            .lines()
+            .map(|line| line.split('#').next().unwrap_or(""))
+            .flat_map(|line| line.split_whitespace());
+
+        let magic = tokens.next().unwrap_or("");
+        if magic != "P3" {
+            return Err(PpmError::InvalidMagicNumber(magic.to_string()));
+        }
+
+        let mut header_value = |name: &str| -> Result<u32, PpmError> {
+            let token = tokens
+                .next()
+                .ok_or_else(|| PpmError::InvalidHeader(format!("missing {}", name)))?;
+            token.parse().map_err(|_| {
+                PpmError::InvalidHeader(format!("{} '{}' is not a number", name, token))
+            })
+        };
+        let width = header_value("width")?;
+        let height = header_value("height")?;
+        let max_value = header_value("max color value")?;
+        if max_value == 0 {
+            return Err(PpmError::InvalidHeader(
+                "max color value must be positive".to_string(),
+            ));
+        }
+
+        // Check there is a sample for every pixel before allocating the canvas, so a header
+        // claiming a huge image can't exhaust memory
+        let samples: Vec<&str> = tokens.collect();
+        let expected = (width as u64)
+            .checked_mul(height as u64)
+            .and_then(|pixels| pixels.checked_mul(3))
+            .ok_or_else(|| {
+                PpmError::InvalidHeader(format!("size {}x{} is too large", width, height))
+            })?;
+        if (samples.len() as u64) < expected {
+            return Err(PpmError::InvalidPixel(
+                "file ended before all pixels were read".to_string(),
+            ));
+        }
+
+        let mut samples = samples.into_iter();
+        let mut sample = || -> Result<f64, PpmError> {
+            // Every sample was counted above
+            let token = samples.next().unwrap_or_default();
+            let value: u32 = token
+                .parse()
+                .map_err(|_| PpmError::InvalidPixel(format!("'{}' is not a number", token)))?;
+            if value > max_value {
+                return Err(PpmError::InvalidPixel(format!(
+                    "{} is larger than the max color value {}",
+                    value, max_value
+                )));
+            }
+            Ok(value as f64 / max_value as f64)
+        };
+
+        let mut canvas = Canvas::new(width, height);
+        for y in 0..height {
+            for x in 0..width {
+                let color = Color::new(sample()?, sample()?, sample()?);
+                canvas.write_pixel(x, y, &color);
+            }
+        }
+        Ok(canvas)
+    }
+
     pub fn write_pixel(&mut self, x: u32, y: u32, color: &Color) {
         if x >= self.width || y >= self.height {
             println!(
@@ -38,6 +168,197 @@ impl Canvas {
         self.pixels[y as usize][x as usize]
     }
 
+    // Bresenham's line algorithm. The line is clipped to the canvas first, so endpoints can be
+    // anywhere, including far off the edge.
+    pub fn draw_line(&mut self, x0: i64, y0: i64, x1: i64, y1: i64, color: &Color) {
+        let Some((mut x0, mut y0, x1, y1)) = self.clip_line(x0, y0, x1, y1) else {
+            return;
+        };
+        let dx = (x1 - x0).abs();
+        let dy = -(y1 - y0).abs();
+        let step_x = if x0 < x1 { 1 } else { -1 };
+        let step_y = if y0 < y1 { 1 } else { -1 };
+        let mut error = dx + dy;
+        loop {
+            self.write_pixel(x0 as u32, y0 as u32, color);
+            if x0 == x1 && y0 == y1 {
+                break;
+            }
+            let doubled = 2 * error;
+            if doubled >= dy {
+                error += dy;
+                x0 += step_x;
+            }
+            if doubled <= dx {
+                error += dx;
+                y0 += step_y;
+            }
+        }
+    }
+
+    // Midpoint circle algorithm. Any part of the circle off the canvas is skipped.
+    pub fn draw_circle(&mut self, cx: i64, cy: i64, r: i64, color: &Color) {
+        let (mut x, mut y) = (r, 0);
+        let mut error = 1 - r;
+        while x >= y {
+            for (dx, dy) in [
+                (x, y),
+                (y, x),
+                (-y, x),
+                (-x, y),
+                (-x, -y),
+                (-y, -x),
+                (y, -x),
+                (x, -y),
+            ] {
+                self.plot(cx + dx, cy + dy, color);
+            }
+            y += 1;
+            if error < 0 {
+                error += 2 * y + 1;
+            } else {
+                x -= 1;
+                error += 2 * (y - x) + 1;
+            }
+        }
+    }
+
+    // Like write_pixel, but quietly ignores points off the canvas
+    fn plot(&mut self, x: i64, y: i64, color: &Color) {
+        if (0..self.width as i64).contains(&x) && (0..self.height as i64).contains(&y) {
+            self.write_pixel(x as u32, y as u32, color);
+        }
+    }
+
+    // Liang-Barsky clipping of a line against the canvas, or None if it misses entirely
+    fn clip_line(&self, x0: i64, y0: i64, x1: i64, y1: i64) -> Option<(i64, i64, i64, i64)> {
+        if self.width == 0 || self.height == 0 {
+            return None;
+        }
+        let (x0, y0, x1, y1) = (x0 as f64, y0 as f64, x1 as f64, y1 as f64);
+        let (dx, dy) = (x1 - x0, y1 - y0);
+        let max_x = (self.width - 1) as f64;
+        let max_y = (self.height - 1) as f64;
+        let (mut t0, mut t1) = (0.0_f64, 1.0_f64);
+        for (p, q) in [(-dx, x0), (dx, max_x - x0), (-dy, y0), (dy, max_y - y0)] {
+            if p == 0.0 {
+                if q < 0.0 {
+                    return None;
+                }
+            } else if p < 0.0 {
+                t0 = t0.max(q / p);
+            } else {
+                t1 = t1.min(q / p);
+            }
+        }
+        if t0 > t1 {
+            return None;
+        }
+        Some((
+            (x0 + t0 * dx).round() as i64,
+            (y0 + t0 * dy).round() as i64,
+            (x0 + t1 * dx).round() as i64,
+            (y0 + t1 * dy).round() as i64,
+        ))
+    }
+
+    // Copies other onto this canvas with its top-left corner at (x, y). Anything that falls
+    // off the edge is dropped.
+    pub fn blit(&mut self, other: &Canvas, x: u32, y: u32) {
+        let width = other.width.min(self.width.saturating_sub(x));
+        let height = other.height.min(self.height.saturating_sub(y));
+        for oy in 0..height {
+            for ox in 0..width {
+                self.write_pixel(x + ox, y + oy, &other.pixel_at(ox, oy));
+            }
+        }
+    }
+
+    // Adds other * weight onto this canvas. Summing several passes with a weight of
+    // 1 / passes averages them.
+    pub fn accumulate(&mut self, other: &Canvas, weight: f64) -> Result<(), CanvasError> {
+        if (self.width, self.height) != (other.width, other.height) {
+            return Err(CanvasError::SizeMismatch(
+                (self.width, self.height),
+                (other.width, other.height),
+            ));
+        }
+        for (row, other_row) in self.pixels.iter_mut().zip(&other.pixels) {
+            for (pixel, other_pixel) in row.iter_mut().zip(other_row) {
+                *pixel = *pixel + *other_pixel * weight;
+            }
+        }
+        Ok(())
+    }
+
+    pub fn scale(&mut self, factor: f64) {
+        for pixel in self.pixels.iter_mut().flatten() {
+            *pixel = *pixel * factor;
+        }
+    }
+
+    // Brightens (positive stops) or darkens (negative stops) the image, doubling or halving
+    // every channel per stop
+    pub fn apply_exposure(&mut self, stops: f64) {
+        self.scale(2.0_f64.powf(stops));
+    }
+
+    // The w x h region with its top-left corner at (x, y), trimmed to fit inside the canvas
+    pub fn crop(&self, x: u32, y: u32, w: u32, h: u32) -> Canvas {
+        let x = x.min(self.width);
+        let y = y.min(self.height);
+        let mut cropped = Canvas::new(w.min(self.width - x), h.min(self.height - y));
+        for cy in 0..cropped.height {
+            for cx in 0..cropped.width {
+                cropped.write_pixel(cx, cy, &self.pixel_at(x + cx, y + cy));
+            }
+        }
+        cropped
+    }
+
+    // Scales to w x h by picking the source pixel under the center of each new pixel
+    pub fn resize_nearest(&self, w: u32, h: u32) -> Canvas {
+        let mut resized = Canvas::new(w, h);
+        if self.width == 0 || self.height == 0 {
+            return resized;
+        }
+        for ry in 0..h {
+            for rx in 0..w {
+                let sx = ((rx as f64 + 0.5) * self.width as f64 / w as f64) as u32;
+                let sy = ((ry as f64 + 0.5) * self.height as f64 / h as f64) as u32;
+                let color = self.pixel_at(sx.min(self.width - 1), sy.min(self.height - 1));
+                resized.write_pixel(rx, ry, &color);
+            }
+        }
+        resized
+    }
+
+    // Reinhard tone mapping, which rolls bright values off smoothly towards 1.0 instead of
+    // clipping each channel on output. exposure scales the colors first.
+    pub fn tone_map(&self, exposure: f64) -> Canvas {
+        self.map_pixels(|pixel| {
+            let exposed = pixel * exposure;
+            Color::new(
+                exposed.red / (1.0 + exposed.red),
+                exposed.green / (1.0 + exposed.green),
+                exposed.blue / (1.0 + exposed.blue),
+            )
+            .clamp(0.0, 1.0)
+        })
+    }
+
+    fn map_pixels<F: Fn(Color) -> Color>(&self, f: F) -> Canvas {
+        Canvas {
+            width: self.width,
+            height: self.height,
+            pixels: self
+                .pixels
+                .iter()
+                .map(|row| row.iter().map(|pixel| f(*pixel)).collect())
+                .collect(),
+        }
+    }
+
     pub fn to_ppm_string(&self) -> String {
         // Start with the header
         // lines 1-3 of ppm are:
@@ -52,9 +373,9 @@ impl Canvas {
         for row in self.pixels.iter() {
             let mut line = String::new();
             for pixel in row.iter() {
-                let r = convert_canvas_color_value_to_decimal_rgb_value(pixel.red);
-                let g = convert_canvas_color_value_to_decimal_rgb_value(pixel.green);
-                let b = convert_canvas_color_value_to_decimal_rgb_value(pixel.blue);
+                let r = convert_canvas_color_value_to_decimal_rgb_value(pixel.red, 1.0);
+                let g = convert_canvas_color_value_to_decimal_rgb_value(pixel.green, 1.0);
+                let b = convert_canvas_color_value_to_decimal_rgb_value(pixel.blue, 1.0);
                 line.push_str(&format!("{} {} {} ", r, g, b));
             }
             line.pop(); // Removes space at end
@@ -86,7 +407,12 @@ impl Canvas {
         ppm
     }
 
-    pub fn to_rgb_image(&self) -> RgbImage  {
+    pub fn to_rgb_image(&self) -> RgbImage {
+        self.to_rgb_image_with_gamma(DEFAULT_GAMMA)
+    }
+
+    // A gamma of 1.0 writes the linear color values unchanged
+    pub fn to_rgb_image_with_gamma(&self, gamma: f64) -> RgbImage {
         let width = self.width;
         let height = self.height;
         let mut img = RgbImage::new(width, height);
@@ -94,9 +420,9 @@ impl Canvas {
         for x in 0..width {
             for y in 0..height {
                 let pixel = self.pixel_at(x, y);
-                let r = convert_canvas_color_value_to_decimal_rgb_value(pixel.red) as u8;
-                let g = convert_canvas_color_value_to_decimal_rgb_value(pixel.green) as u8;
-                let b = convert_canvas_color_value_to_decimal_rgb_value(pixel.blue) as u8;
+                let r = convert_canvas_color_value_to_decimal_rgb_value(pixel.red, gamma) as u8;
+                let g = convert_canvas_color_value_to_decimal_rgb_value(pixel.green, gamma) as u8;
+                let b = convert_canvas_color_value_to_decimal_rgb_value(pixel.blue, gamma) as u8;
                 img.put_pixel(x, y, Rgb([r, g, b]));
             }
         }
@@ -104,6 +430,23 @@ impl Canvas {
         img
     }
 
+    // Uses the red channel of coverage as each pixel's opacity, so the image can be laid over
+    // another one. Camera::render_with_coverage makes one alongside a render.
+    pub fn to_rgba_image(&self, coverage: &Canvas) -> Result<RgbaImage, ImageError> {
+        if (self.width, self.height) != (coverage.width, coverage.height) {
+            return Err(ImageError::Parameter(ParameterError::from_kind(
+                ParameterErrorKind::DimensionMismatch,
+            )));
+        }
+        let rgb = self.to_rgb_image();
+        let mut img = RgbaImage::new(self.width, self.height);
+        for (x, y, &Rgb([r, g, b])) in rgb.enumerate_pixels() {
+            let alpha = (coverage.pixel_at(x, y).red.clamp(0.0, 1.0) * 255.0).round() as u8;
+            img.put_pixel(x, y, Rgba([r, g, b, alpha]));
+        }
+        Ok(img)
+    }
+
     pub fn to_jpeg<P: AsRef<Path>>(&self, path: P) -> Result<(), ImageError> {
         let img = self.to_rgb_image();
         let mut buffer = File::create(path)?;
@@ -116,10 +459,60 @@ impl Canvas {
         file.write_all(self.to_ppm_string().as_bytes())?;
         Ok(())
     }
+
+    pub fn to_png<P: AsRef<Path>>(&self, path: P) -> Result<(), ImageError> {
+        let img = self.to_rgb_image();
+        let mut buffer = File::create(path)?;
+        img.write_to(&mut buffer, ImageOutputFormat::Png)?;
+        Ok(())
+    }
+
+    pub fn to_png_rgba<P: AsRef<Path>>(
+        &self,
+        path: P,
+        coverage: &Canvas,
+    ) -> Result<(), ImageError> {
+        let img = self.to_rgba_image(coverage)?;
+        let mut buffer = File::create(path)?;
+        img.write_to(&mut buffer, ImageOutputFormat::Png)?;
+        Ok(())
+    }
+
+    // Picks the output format from the file extension
+    pub fn save<P: AsRef<Path>>(&self, path: P) -> Result<(), ImageError> {
+        let path = path.as_ref();
+        let extension = path
+            .extension()
+            .and_then(|e| e.to_str())
+            .map(|e| e.to_lowercase());
+        match extension.as_deref() {
+            Some("png") => self.to_png(path),
+            Some("jpg") | Some("jpeg") => self.to_jpeg(path),
+            Some("ppm") => Ok(self.to_ppm(path)?),
+            _ => Err(ImageError::Unsupported(
+                UnsupportedError::from_format_and_kind(
+                    ImageFormatHint::PathExtension(path.to_path_buf()),
+                    UnsupportedErrorKind::Format(ImageFormatHint::PathExtension(
+                        path.to_path_buf(),
+                    )),
+                ),
+            )),
+        }
+    }
+
+    // Like save, but tone maps the image first so over-bright pixels roll off smoothly
+    // instead of clipping. The canvas itself is left as it was.
+    pub fn save_tone_mapped<P: AsRef<Path>>(
+        &self,
+        path: P,
+        exposure: f64,
+    ) -> Result<(), ImageError> {
+        self.tone_map(exposure).save(path)
+    }
 }
 
-fn convert_canvas_color_value_to_decimal_rgb_value(value: f64) -> u32 {
-    let ppm_value = (value * 255.0).round() as u32;
+fn convert_canvas_color_value_to_decimal_rgb_value(value: f64, gamma: f64) -> u32 {
+    let ppm_value = (value.max(0.0).powf(1.0 / gamma) * 255.0).round() as u32;
     if ppm_value > MAX_COLOR_VALUE {
         MAX_COLOR_VALUE
     } else {
@@ -131,6 +524,7 @@ fn convert_canvas_color_value_to_decimal_rgb_value(value: f64) -> u32 {
 mod tests {
     use crate::canvas::Canvas;
     use crate::canvas::Color;
+    use crate::canvas::{CanvasError, PpmError};
 
     #[test]
     fn creating_a_canvas() {
@@ -221,4 +615,355 @@ mod tests {
         let ppm = c.to_ppm_string();
         assert_eq!(ppm.chars().last(), Some('\n'));
     }
+
+    #[test]
+    fn save_picks_format_from_extension() {
+        let mut c = Canvas::new(5, 3);
+        c.write_pixel(2, 1, &Color::new(1.0, 0.5, 0.0));
+        let dir = std::env::temp_dir();
+
+        let png = dir.join("raytracer_canvas_save_test.png");
+        c.save(&png).unwrap();
+        let bytes = std::fs::read(&png).unwrap();
+        assert_eq!(&bytes[1..4], b"PNG");
+
+        let ppm = dir.join("raytracer_canvas_save_test.ppm");
+        c.save(&ppm).unwrap();
+        assert_eq!(std::fs::read_to_string(&ppm).unwrap(), c.to_ppm_string());
+
+        let jpg = dir.join("raytracer_canvas_save_test.jpg");
+        c.save(&jpg).unwrap();
+        assert_eq!(&std::fs::read(&jpg).unwrap()[0..2], &[0xFF, 0xD8]);
+
+        for path in [png, ppm, jpg] {
+            let _ = std::fs::remove_file(path);
+        }
+    }
+
+    #[test]
+    fn saving_tone_mapped_keeps_bright_pixels_below_white() {
+        let mut c = Canvas::new(1, 1);
+        c.write_pixel(0, 0, &Color::new(4.0, 2.0, 1.0));
+        let path = std::env::temp_dir().join("raytracer_canvas_save_tone_mapped_test.ppm");
+
+        c.save(&path).unwrap();
+        let clipped = Canvas::from_ppm_file(&path).unwrap().pixel_at(0, 0);
+        assert_eq!(clipped, Color::white());
+
+        c.save_tone_mapped(&path, 1.0).unwrap();
+        let tone_mapped = Canvas::from_ppm_file(&path).unwrap().pixel_at(0, 0);
+        let _ = std::fs::remove_file(&path);
+        assert_eq!(
+            tone_mapped,
+            Color::new(204.0 / 255.0, 170.0 / 255.0, 128.0 / 255.0)
+        );
+        assert_eq!(c.pixel_at(0, 0), Color::new(4.0, 2.0, 1.0));
+    }
+
+    #[test]
+    fn save_rejects_unknown_extensions() {
+        let c = Canvas::new(5, 3);
+        let path = std::env::temp_dir().join("raytracer_canvas_save_test.bmp3");
+        assert!(c.save(&path).is_err());
+        assert!(!path.exists());
+    }
+
+    #[test]
+    fn reading_file_with_wrong_magic_number() {
+        let ppm = "P32\n1 1\n255\n0 0 0\n";
+        assert!(matches!(
+            Canvas::from_ppm_string(ppm),
+            Err(PpmError::InvalidMagicNumber(_))
+        ));
+    }
+
+    #[test]
+    fn reading_ppm_returns_canvas_of_right_size() {
+        let ppm = "P3\n10 2\n255\n".to_string() + &"0 0 0 ".repeat(20);
+        let canvas = Canvas::from_ppm_string(&ppm).unwrap();
+        assert_eq!(canvas.width, 10);
+        assert_eq!(canvas.height, 2);
+    }
+
+    #[test]
+    fn reading_pixel_data_from_ppm() {
+        let ppm = "P3\n4 3\n255\n\
+                   255 127 0  0 127 255  127 255 0  255 255 255\n\
+                   0 0 0  255 0 0  0 255 0  0 0 255\n\
+                   255 255 0  0 255 255  255 0 255  127 127 127\n";
+        let canvas = Canvas::from_ppm_string(ppm).unwrap();
+        let cases = [
+            (0, 0, Color::new(1.0, 0.49804, 0.0)),
+            (1, 0, Color::new(0.0, 0.49804, 1.0)),
+            (2, 0, Color::new(0.49804, 1.0, 0.0)),
+            (3, 0, Color::new(1.0, 1.0, 1.0)),
+            (0, 1, Color::new(0.0, 0.0, 0.0)),
+            (1, 1, Color::new(1.0, 0.0, 0.0)),
+            (2, 1, Color::new(0.0, 1.0, 0.0)),
+            (3, 1, Color::new(0.0, 0.0, 1.0)),
+            (0, 2, Color::new(1.0, 1.0, 0.0)),
+            (1, 2, Color::new(0.0, 1.0, 1.0)),
+            (2, 2, Color::new(1.0, 0.0, 1.0)),
+            (3, 2, Color::new(0.49804, 0.49804, 0.49804)),
+        ];
+        for (x, y, expected) in cases {
+            assert_eq!(canvas.pixel_at(x, y), expected);
+        }
+    }
+
+    #[test]
+    fn ppm_parsing_ignores_comment_lines() {
+        let ppm = "P3\n# this is a comment\n2 1\n# this, too\n255\n# another comment\n\
+                   255 255 255\n# oh, no, comments in the pixel data!\n255 0 255\n";
+        let canvas = Canvas::from_ppm_string(ppm).unwrap();
+        assert_eq!(canvas.pixel_at(0, 0), Color::new(1.0, 1.0, 1.0));
+        assert_eq!(canvas.pixel_at(1, 0), Color::new(1.0, 0.0, 1.0));
+    }
+
+    #[test]
+    fn ppm_parsing_allows_rgb_triple_to_span_lines() {
+        let ppm = "P3\n1 1\n255\n51\n153\n\n204\n";
+        let canvas = Canvas::from_ppm_string(ppm).unwrap();
+        assert_eq!(canvas.pixel_at(0, 0), Color::new(0.2, 0.6, 0.8));
+    }
+
+    #[test]
+    fn ppm_parsing_respects_scale_setting() {
+        let ppm = "P3\n2 2\n100\n100 100 100  50 50 50\n75 50 25  0 0 0\n";
+        let canvas = Canvas::from_ppm_string(ppm).unwrap();
+        assert_eq!(canvas.pixel_at(0, 1), Color::new(0.75, 0.5, 0.25));
+    }
+
+    #[test]
+    fn ppm_parsing_rejects_truncated_and_malformed_data() {
+        assert!(matches!(
+            Canvas::from_ppm_string("P3\n2 1\n255\n0 0 0 0 0\n"),
+            Err(PpmError::InvalidPixel(_))
+        ));
+        assert!(matches!(
+            Canvas::from_ppm_string("P3\n2 1\n255\n0 0 0 0 0 256\n"),
+            Err(PpmError::InvalidPixel(_))
+        ));
+        assert!(matches!(
+            Canvas::from_ppm_string("P3\nwide 1\n255\n"),
+            Err(PpmError::InvalidHeader(_))
+        ));
+    }
+
+    #[test]
+    fn ppm_parsing_rejects_huge_headers_without_allocating() {
+        assert!(matches!(
+            Canvas::from_ppm_string("P3\n100000 100000\n255\n0 0 0\n"),
+            Err(PpmError::InvalidPixel(_))
+        ));
+        assert!(matches!(
+            Canvas::from_ppm_string("P3\n4294967295 4294967295\n255\n0 0 0\n"),
+            Err(PpmError::InvalidHeader(_))
+        ));
+    }
+
+    #[test]
+    fn ppm_round_trips_through_canvas() {
+        let mut c = Canvas::new(3, 2);
+        c.write_pixel(1, 1, &Color::new(1.0, 0.2, 0.6));
+        let path = std::env::temp_dir().join("raytracer_canvas_round_trip_test.ppm");
+        c.to_ppm(&path).unwrap();
+        let read = Canvas::from_ppm_file(&path).unwrap();
+        let _ = std::fs::remove_file(&path);
+        assert_eq!(read.pixels, c.pixels);
+    }
+
+    #[test]
+    fn tone_mapping_rolls_off_bright_colors() {
+        let mut c = Canvas::new(2, 1);
+        c.write_pixel(0, 0, &Color::new(1.0, 3.0, 0.0));
+        c.write_pixel(1, 0, &Color::new(9.0, -1.0, 0.25));
+        let mapped = c.tone_map(1.0);
+        assert_eq!(mapped.pixel_at(0, 0), Color::new(0.5, 0.75, 0.0));
+        assert_eq!(mapped.pixel_at(1, 0), Color::new(0.9, 0.0, 0.2));
+        // The original canvas is left untouched
+        assert_eq!(c.pixel_at(0, 0), Color::new(1.0, 3.0, 0.0));
+    }
+
+    #[test]
+    fn tone_mapping_applies_exposure_first() {
+        let mut c = Canvas::new(1, 1);
+        c.write_pixel(0, 0, &Color::new(0.5, 0.5, 0.5));
+        let mapped = c.tone_map(2.0);
+        assert_eq!(mapped.pixel_at(0, 0), Color::new(0.5, 0.5, 0.5));
+    }
+
+    #[test]
+    fn rgb_image_applies_gamma() {
+        let mut c = Canvas::new(2, 1);
+        c.write_pixel(0, 0, &Color::new(0.25, 0.5, 1.0));
+        c.write_pixel(1, 0, &Color::new(-0.5, 0.0, 1.5));
+        let linear = c.to_rgb_image_with_gamma(1.0);
+        assert_eq!(linear.get_pixel(0, 0).0, [64, 128, 255]);
+        assert_eq!(linear.get_pixel(1, 0).0, [0, 0, 255]);
+        let corrected = c.to_rgb_image_with_gamma(2.0);
+        assert_eq!(corrected.get_pixel(0, 0).0, [128, 180, 255]);
+        assert_eq!(c.to_rgb_image().get_pixel(0, 0).0, [136, 186, 255]);
+    }
+
+    #[test]
+    fn blitting_a_canvas_clips_at_the_edges() {
+        let mut c = Canvas::new(4, 3);
+        let mut patch = Canvas::new(2, 2);
+        let red = Color::new(1.0, 0.0, 0.0);
+        for y in 0..2 {
+            for x in 0..2 {
+                patch.write_pixel(x, y, &red);
+            }
+        }
+        c.blit(&patch, 3, 2);
+        assert_eq!(c.pixel_at(3, 2), red);
+        assert_eq!(c.pixel_at(2, 2), Color::black());
+        assert_eq!(c.pixel_at(3, 1), Color::black());
+    }
+
+    #[test]
+    fn drawing_lines() {
+        let mut c = Canvas::new(5, 5);
+        let red = Color::new(1.0, 0.0, 0.0);
+        c.draw_line(0, 0, 4, 4, &red);
+        for i in 0..5 {
+            assert_eq!(c.pixel_at(i, i), red);
+        }
+        assert_eq!(c.pixel_at(1, 0), Color::black());
+
+        let mut c = Canvas::new(5, 5);
+        c.draw_line(4, 2, 0, 2, &red);
+        for x in 0..5 {
+            assert_eq!(c.pixel_at(x, 2), red);
+        }
+        assert_eq!(c.pixel_at(2, 1), Color::black());
+    }
+
+    #[test]
+    fn lines_are_clipped_to_the_canvas() {
+        let mut c = Canvas::new(5, 5);
+        let red = Color::new(1.0, 0.0, 0.0);
+        c.draw_line(-1_000_000, 2, 1_000_000, 2, &red);
+        for x in 0..5 {
+            assert_eq!(c.pixel_at(x, 2), red);
+        }
+        // Entirely off the canvas
+        c.draw_line(-10, -1, 10, -1, &Color::white());
+        assert!(c.pixels.iter().flatten().all(|p| *p != Color::white()));
+    }
+
+    #[test]
+    fn drawing_circles() {
+        let mut c = Canvas::new(11, 11);
+        let red = Color::new(1.0, 0.0, 0.0);
+        c.draw_circle(5, 5, 3, &red);
+        for (x, y) in [(8, 5), (2, 5), (5, 8), (5, 2), (7, 7), (3, 3)] {
+            assert_eq!(c.pixel_at(x, y), red);
+        }
+        assert_eq!(c.pixel_at(5, 5), Color::black());
+        assert_eq!(c.pixel_at(9, 5), Color::black());
+    }
+
+    #[test]
+    fn circles_are_clipped_to_the_canvas() {
+        let mut c = Canvas::new(5, 5);
+        let red = Color::new(1.0, 0.0, 0.0);
+        c.draw_circle(0, 0, 3, &red);
+        assert_eq!(c.pixel_at(3, 0), red);
+        assert_eq!(c.pixel_at(0, 3), red);
+    }
+
+    #[test]
+    fn accumulating_passes_averages_them() {
+        let mut first = Canvas::new(2, 2);
+        first.write_pixel(0, 0, &Color::new(1.0, 0.0, 0.0));
+        let mut second = Canvas::new(2, 2);
+        second.write_pixel(0, 0, &Color::new(0.0, 0.0, 1.0));
+        second.write_pixel(1, 1, &Color::white());
+
+        let mut sum = Canvas::new(2, 2);
+        sum.accumulate(&first, 0.5).unwrap();
+        sum.accumulate(&second, 0.5).unwrap();
+        assert_eq!(sum.pixel_at(0, 0), Color::new(0.5, 0.0, 0.5));
+        assert_eq!(sum.pixel_at(1, 1), Color::new(0.5, 0.5, 0.5));
+
+        sum.scale(2.0);
+        assert_eq!(sum.pixel_at(1, 1), Color::white());
+    }
+
+    #[test]
+    fn accumulating_canvases_of_different_sizes_fails() {
+        let mut canvas = Canvas::new(2, 2);
+        assert_eq!(
+            canvas.accumulate(&Canvas::new(3, 2), 1.0),
+            Err(CanvasError::SizeMismatch((2, 2), (3, 2)))
+        );
+    }
+
+    #[test]
+    fn cropped_pixels_match_the_original() {
+        let mut canvas = Canvas::new(10, 8);
+        canvas.write_pixel(4, 5, &Color::red());
+        let cropped = canvas.crop(3, 2, 4, 4);
+        assert_eq!((cropped.width, cropped.height), (4, 4));
+        assert_eq!(cropped.pixel_at(1, 3), Color::red());
+        assert_eq!(cropped.pixel_at(0, 0), Color::black());
+    }
+
+    #[test]
+    fn cropping_is_clamped_to_the_canvas() {
+        let canvas = Canvas::new(10, 8);
+        let cropped = canvas.crop(8, 6, 5, 5);
+        assert_eq!((cropped.width, cropped.height), (2, 2));
+        let outside = canvas.crop(20, 20, 5, 5);
+        assert_eq!((outside.width, outside.height), (0, 0));
+    }
+
+    #[test]
+    fn resizing_with_nearest_neighbour() {
+        let mut canvas = Canvas::new(4, 4);
+        canvas.write_pixel(2, 0, &Color::red());
+        canvas.write_pixel(3, 1, &Color::red());
+        let smaller = canvas.resize_nearest(2, 2);
+        assert_eq!((smaller.width, smaller.height), (2, 2));
+        assert_eq!(smaller.pixel_at(1, 0), Color::red());
+        assert_eq!(smaller.pixel_at(0, 0), Color::black());
+
+        let larger = smaller.resize_nearest(4, 4);
+        assert_eq!(larger.pixel_at(2, 0), Color::red());
+        assert_eq!(larger.pixel_at(3, 1), Color::red());
+        assert_eq!(larger.pixel_at(1, 1), Color::black());
+    }
+
+    #[test]
+    fn rgba_image_takes_alpha_from_coverage() {
+        let mut c = Canvas::new(3, 1);
+        c.write_pixel(0, 0, &Color::new(1.0, 0.0, 0.0));
+        c.write_pixel(1, 0, &Color::new(1.0, 0.0, 0.0));
+        let mut coverage = Canvas::new(3, 1);
+        coverage.write_pixel(0, 0, &Color::white());
+        coverage.write_pixel(1, 0, &Color::new(0.5, 0.5, 0.5));
+        let img = c.to_rgba_image(&coverage).unwrap();
+        assert_eq!(img.get_pixel(0, 0).0, [255, 0, 0, 255]);
+        assert_eq!(img.get_pixel(1, 0).0, [255, 0, 0, 128]);
+        assert_eq!(img.get_pixel(2, 0).0, [0, 0, 0, 0]);
+    }
+
+    #[test]
+    fn rgba_image_needs_coverage_of_the_same_size() {
+        let c = Canvas::new(3, 1);
+        assert!(c.to_rgba_image(&Canvas::new(1, 3)).is_err());
+    }
+
+    #[test]
+    fn applying_exposure_in_stops() {
+        let mut c = Canvas::new(2, 1);
+        c.write_pixel(0, 0, &Color::new(0.25, 0.5, 1.0));
+        c.apply_exposure(1.0);
+        assert_eq!(c.pixel_at(0, 0), Color::new(0.5, 1.0, 2.0));
+        c.apply_exposure(-2.0);
+        assert_eq!(c.pixel_at(0, 0), Color::new(0.125, 0.25, 0.5));
+        assert_eq!(c.pixel_at(1, 0), Color::black());
+    }
 }
@@ -1,5 +1,6 @@
-use crate::floats::float_equal;
-use std::ops::{Add, Mul, Sub};
+use crate::floats::{approx_equal, EPSILON};
+use std::fmt;
+use std::ops::{Add, Div, Mul, Sub};
 
 #[derive(Debug, Clone, Copy)]
 pub struct Color {
@@ -20,6 +21,107 @@ impl Color {
     pub fn white() -> Color {
         Color::new(1.0, 1.0, 1.0)
     }
+
+    pub fn red() -> Color {
+        Color::new(1.0, 0.0, 0.0)
+    }
+
+    pub fn green() -> Color {
+        Color::new(0.0, 1.0, 0.0)
+    }
+
+    pub fn blue() -> Color {
+        Color::new(0.0, 0.0, 1.0)
+    }
+
+    // Accepts #RRGGBB or the #RGB shorthand, where each digit is doubled
+    pub fn from_hex(s: &str) -> Result<Color, ColorError> {
+        let invalid = || ColorError::InvalidHex(s.to_string());
+        let digits = s.strip_prefix('#').ok_or_else(invalid)?;
+        if !digits.chars().all(|c| c.is_ascii_hexdigit()) {
+            return Err(invalid());
+        }
+        let channels: Vec<u8> = match digits.len() {
+            3 => digits
+                .chars()
+                .map(|c| u8::from_str_radix(&c.to_string().repeat(2), 16))
+                .collect::<Result<_, _>>()
+                .map_err(|_| invalid())?,
+            6 => (0..6)
+                .step_by(2)
+                .map(|i| u8::from_str_radix(&digits[i..i + 2], 16))
+                .collect::<Result<_, _>>()
+                .map_err(|_| invalid())?,
+            _ => return Err(invalid()),
+        };
+        Ok(Color::new(
+            channels[0] as f64 / 255.0,
+            channels[1] as f64 / 255.0,
+            channels[2] as f64 / 255.0,
+        ))
+    }
+
+    // Blends from self at t = 0.0 to other at t = 1.0, with t clamped to that range
+    pub fn lerp(&self, other: &Color, t: f64) -> Color {
+        let t = t.clamp(0.0, 1.0);
+        *self * (1.0 - t) + *other * t
+    }
+
+    // Component-wise product, the same as multiplying two colors
+    pub fn hadamard(&self, other: &Color) -> Color {
+        *self * *other
+    }
+
+    // Adds the colors but caps each channel at 1.0
+    pub fn saturating_add(&self, other: &Color) -> Color {
+        Color::new(
+            (self.red + other.red).min(1.0),
+            (self.green + other.green).min(1.0),
+            (self.blue + other.blue).min(1.0),
+        )
+    }
+
+    // Like ==, but with a tolerance of epsilon rather than the fixed EPSILON
+    pub fn approx_eq(&self, other: &Color, epsilon: f64) -> bool {
+        approx_equal(self.red, other.red, epsilon)
+            && approx_equal(self.green, other.green, epsilon)
+            && approx_equal(self.blue, other.blue, epsilon)
+    }
+
+    pub fn clamp(&self, min: f64, max: f64) -> Color {
+        Color::new(
+            self.red.clamp(min, max),
+            self.green.clamp(min, max),
+            self.blue.clamp(min, max),
+        )
+    }
+}
+
+#[derive(Debug, PartialEq)]
+pub enum ColorError {
+    InvalidHex(String),
+}
+
+impl fmt::Display for ColorError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ColorError::InvalidHex(s) => write!(f, "Invalid hex color: {}", s),
+        }
+    }
+}
+
+impl std::error::Error for ColorError {}
+
+// Prints as rgb(r, g, b) with 3 decimal places unless a precision is given
+impl fmt::Display for Color {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let precision = f.precision().unwrap_or(3);
+        write!(
+            f,
+            "rgb({:.*}, {:.*}, {:.*})",
+            precision, self.red, precision, self.green, precision, self.blue
+        )
+    }
 }
 
 impl Add for Color {
@@ -53,6 +155,17 @@ impl Mul<f64> for Color {
     }
 }
 
+// Dividing by zero leaves the color unchanged rather than filling it with infinities
+impl Div<f64> for Color {
+    type Output = Self;
+    fn div(self, scalar: f64) -> Self::Output {
+        if scalar == 0.0 {
+            return self;
+        }
+        Color::new(self.red / scalar, self.green / scalar, self.blue / scalar)
+    }
+}
+
 // Hadamard product
 // Does not defer to tuple impl, since the tuple impl can't do this.
 impl Mul<Color> for Color {
@@ -68,15 +181,13 @@ impl Mul<Color> for Color {
 
 impl PartialEq for Color {
     fn eq(&self, other: &Self) -> bool {
-        float_equal(self.red, other.red)
-            && float_equal(self.green, other.green)
-            && float_equal(self.blue, other.blue)
+        self.approx_eq(other, EPSILON)
     }
 }
 
 #[cfg(test)]
 mod tests {
-    use crate::color::Color;
+    use crate::color::{Color, ColorError};
 
     #[test]
     fn colors_are_red_green_blue_tuples() {
@@ -116,4 +227,86 @@ mod tests {
         let result = color1 * color2;
         assert_eq!(result, Color::new(0.9, 0.2, 0.04));
     }
+
+    #[test]
+    fn hadamard_product_matches_multiplication() {
+        let color1 = Color::new(1.0, 0.2, 0.4);
+        let color2 = Color::new(0.9, 1.0, 0.1);
+        assert_eq!(color1.hadamard(&color2), color1 * color2);
+    }
+
+    #[test]
+    fn colors_can_be_divided_by_a_scalar() {
+        let sum = Color::new(0.4, 1.0, 2.0) + Color::new(0.2, 0.0, 1.0);
+        assert_eq!(sum / 2.0, Color::new(0.3, 0.5, 1.5));
+    }
+
+    #[test]
+    fn dividing_a_color_by_zero_leaves_it_unchanged() {
+        let color = Color::new(0.2, 0.3, 0.4);
+        assert_eq!(color / 0.0, color);
+    }
+
+    #[test]
+    fn saturating_add_caps_each_channel() {
+        let color1 = Color::new(0.9, 0.6, 0.2);
+        let color2 = Color::new(0.7, 0.1, 0.3);
+        assert_eq!(color1.saturating_add(&color2), Color::new(1.0, 0.7, 0.5));
+    }
+
+    #[test]
+    fn displaying_a_color() {
+        let color = Color::new(0.2, 0.45, 1.0);
+        assert_eq!(format!("{}", color), "rgb(0.200, 0.450, 1.000)");
+        assert_eq!(format!("{:.1}", color), "rgb(0.2, 0.5, 1.0)");
+    }
+
+    #[test]
+    fn comparing_colors_with_a_custom_tolerance() {
+        let color1 = Color::new(0.5, 0.5, 0.5);
+        let color2 = Color::new(0.5, 0.52, 0.5);
+        assert!(color1.approx_eq(&color2, 0.05));
+        assert!(!color1.approx_eq(&color2, 0.01));
+    }
+
+    #[test]
+    fn clamping_a_color() {
+        let color = Color::new(-0.5, 0.4, 1.7);
+        assert_eq!(color.clamp(0.0, 1.0), Color::new(0.0, 0.4, 1.0));
+    }
+
+    #[test]
+    fn parsing_hex_colors() {
+        assert_eq!(Color::from_hex("#336699"), Ok(Color::new(0.2, 0.4, 0.6)));
+        assert_eq!(Color::from_hex("#FF8800"), Color::from_hex("#f80"));
+        assert_eq!(Color::from_hex("#ffffff"), Ok(Color::white()));
+    }
+
+    #[test]
+    fn invalid_hex_colors_are_rejected() {
+        for s in ["336699", "#3366", "#33669g", "#", "#ééé"] {
+            assert_eq!(
+                Color::from_hex(s),
+                Err(ColorError::InvalidHex(s.to_string()))
+            );
+        }
+    }
+
+    #[test]
+    fn named_colors() {
+        assert_eq!(Color::red(), Color::new(1.0, 0.0, 0.0));
+        assert_eq!(Color::green(), Color::new(0.0, 1.0, 0.0));
+        assert_eq!(Color::blue(), Color::new(0.0, 0.0, 1.0));
+    }
+
+    #[test]
+    fn interpolating_between_colors() {
+        let from = Color::new(0.0, 0.5, 1.0);
+        let to = Color::new(1.0, 1.0, 0.0);
+        assert_eq!(from.lerp(&to, 0.0), from);
+        assert_eq!(from.lerp(&to, 0.5), Color::new(0.5, 0.75, 0.5));
+        assert_eq!(from.lerp(&to, 1.0), to);
+        assert_eq!(from.lerp(&to, 2.0), to);
+        assert_eq!(from.lerp(&to, -1.0), from);
+    }
 }
@@ -0,0 +1,122 @@
+use crate::bounds::Bounds;
+use crate::floats::EPSILON;
+use crate::materials::Material;
+use crate::matrices::Matrix4;
+use crate::rays::Ray;
+use crate::transformations::Transform;
+use crate::tuples::{Point, Tuple, Vector};
+
+// A flat circle in the xz plane, centered on the origin. A non-zero inner_radius cuts a
+// hole out of the middle, making it a ring.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct Disk {
+    pub inner_radius: f64,
+    pub outer_radius: f64,
+    pub transform: Transform,
+    pub material: Material,
+}
+
+impl Disk {
+    pub fn new(inner_radius: f64, outer_radius: f64) -> Disk {
+        Disk {
+            inner_radius,
+            outer_radius,
+            transform: Transform::default(),
+            material: Material::default(),
+        }
+    }
+
+    // Returns list of time values where the ray intersects the disk
+    pub fn intersect(&self, ray: Ray) -> Vec<f64> {
+        let ray = ray.transform(self.transform.inverse());
+
+        // Ray is parallel to the disk's plane
+        if ray.direction.y.abs() < EPSILON {
+            return vec![];
+        }
+
+        let t = -ray.origin.y / ray.direction.y;
+        let point = ray.position(t);
+        let distance_squared = point.x.powi(2) + point.z.powi(2);
+        if distance_squared < self.inner_radius.powi(2)
+            || distance_squared > self.outer_radius.powi(2)
+        {
+            return vec![];
+        }
+        vec![t]
+    }
+
+    // The normal is the same everywhere on a flat disk
+    pub fn normal_at(&self, _point: Point) -> Vector {
+        let world_normal = self.transform.inverse().transpose() * Vector::new(0.0, 1.0, 0.0);
+        world_normal.normalize()
+    }
+
+    pub fn bounds(&self) -> Bounds {
+        let r = self.outer_radius;
+        Bounds::new(Point::new(-r, 0.0, -r), Point::new(r, 0.0, r))
+    }
+
+    pub fn set_transform(&mut self, transformation: Matrix4) {
+        self.transform.set_matrix(transformation);
+    }
+
+    pub fn set_material(&mut self, material: Material) {
+        self.material = material;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::disk::Disk;
+    use crate::matrices::Matrix4;
+    use crate::objects::{Intersectable, Object};
+    use crate::rays::Ray;
+    use crate::tuples::{Point, Tuple, Vector};
+    use std::f64::consts::PI;
+
+    #[test]
+    fn ray_hits_a_disk() {
+        let disk = Disk::new(0.0, 1.0);
+        let ray = Ray::new(Point::new(0.5, 2.0, 0.5), Vector::new(0.0, -1.0, 0.0));
+        assert_eq!(disk.intersect(ray), vec![2.0]);
+    }
+
+    #[test]
+    fn ray_misses_outside_the_outer_radius() {
+        let disk = Disk::new(0.0, 1.0);
+        let ray = Ray::new(Point::new(1.0, 1.0, 0.5), Vector::new(0.0, -1.0, 0.0));
+        assert!(disk.intersect(ray).is_empty());
+    }
+
+    #[test]
+    fn ray_passes_through_the_hole_in_a_ring() {
+        let ring = Disk::new(0.2, 1.0);
+        let through_hole = Ray::new(Point::new(0.1, 1.0, 0.0), Vector::new(0.0, -1.0, 0.0));
+        let on_ring = Ray::new(Point::new(0.5, 1.0, 0.0), Vector::new(0.0, -1.0, 0.0));
+        assert!(ring.intersect(through_hole).is_empty());
+        assert_eq!(ring.intersect(on_ring), vec![1.0]);
+    }
+
+    #[test]
+    fn ray_parallel_to_a_disk_misses() {
+        let disk = Disk::new(0.0, 1.0);
+        let ray = Ray::new(Point::new(0.0, 0.0, -2.0), Vector::new(0.0, 0.0, 1.0));
+        assert!(disk.intersect(ray).is_empty());
+    }
+
+    #[test]
+    fn normal_of_a_transformed_disk() {
+        let mut disk = Object::disk(0.0, 1.0);
+        disk.set_transform(Matrix4::rotate_x(PI / 2.0));
+        let normal = disk.normal_at(Point::new(0.0, 0.0, 0.0));
+        assert_eq!(normal, Vector::new(0.0, 0.0, 1.0));
+    }
+
+    #[test]
+    fn disk_bounds_cover_the_outer_radius() {
+        let bounds = Disk::new(0.2, 1.5).bounds();
+        assert_eq!(bounds.min, Point::new(-1.5, 0.0, -1.5));
+        assert_eq!(bounds.max, Point::new(1.5, 0.0, 1.5));
+    }
+}
@@ -2,7 +2,11 @@
 pub const EPSILON: f64 = 0.00001;
 
 pub fn float_equal(a: f64, b: f64) -> bool {
-    (a - b).abs() < EPSILON
+    approx_equal(a, b, EPSILON)
+}
+
+pub fn approx_equal(a: f64, b: f64, epsilon: f64) -> bool {
+    (a - b).abs() < epsilon
 }
 
 #[cfg(test)]
@@ -14,4 +18,11 @@ mod test {
         assert!(super::float_equal(0.00001, 0.000009));
         assert!(!super::float_equal(0.00001, 0.00002));
     }
+
+    #[test]
+    fn approx_equal() {
+        assert!(super::approx_equal(1.0, 1.0 + 1e-10, 1e-9));
+        assert!(!super::approx_equal(1.0, 1.0 + 1e-8, 1e-9));
+        assert!(super::approx_equal(1.0, 1.05, 0.1));
+    }
 }
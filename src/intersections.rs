@@ -7,6 +7,8 @@ use crate::tuples::{Point, Vector};
 pub struct Intersection<'a> {
     pub t: f64,
     pub object: &'a Object,
+    // Barycentric coordinates of the hit, only set for triangles
+    pub uv: Option<(f64, f64)>,
 }
 
 pub struct Computations {
@@ -17,11 +19,25 @@ pub struct Computations {
     pub normal_vector: Vector,
     pub inside: bool,
     pub over_point: Point,
+    // When the ray was cast, so shadow and occlusion rays see moving objects where it did
+    pub time_of_ray: f64,
 }
 
 impl Intersection<'_> {
-    pub fn new(t: f64, object: &Object) -> Intersection {
-        Intersection { t, object }
+    pub fn new(t: f64, object: &Object) -> Intersection<'_> {
+        Intersection {
+            t,
+            object,
+            uv: None,
+        }
+    }
+
+    pub fn with_uv(t: f64, object: &Object, u: f64, v: f64) -> Intersection<'_> {
+        Intersection {
+            t,
+            object,
+            uv: Some((u, v)),
+        }
     }
 
     pub fn sort_intersections(mut intersections: Vec<f64>) -> Vec<f64> {
@@ -35,7 +51,21 @@ impl Intersection<'_> {
     }
 
     pub fn prepare_computations(&self, ray: Ray) -> Computations {
-        let normal_vector = self.object.normal_at(ray.position(self.t));
+        self.prepare_computations_with_bias(ray, EPSILON)
+    }
+
+    // bias is how far over_point is pushed off the surface, to keep it from shadowing itself
+    pub fn prepare_computations_with_bias(&self, ray: Ray, bias: f64) -> Computations {
+        // Moving objects work out the normal where they were at time 0.0, then carry it
+        // along to where they are at the ray's time
+        let normal_vector = match self.object.motion_transform(ray.time) {
+            Some(transform) => {
+                let moved_point = transform * ray.position(self.t);
+                let normal = self.object.normal_at_hit(moved_point, self);
+                (transform.transpose() * normal).normalize()
+            }
+            None => self.object.normal_at_hit(ray.position(self.t), self),
+        };
         let eye_vector = -ray.direction;
 
         let (inside, normal_vector) = if normal_vector.dot(&eye_vector) < 0.0 {
@@ -45,7 +75,7 @@ impl Intersection<'_> {
         };
         let point = ray.position(self.t);
 
-        let over_point = point + normal_vector * EPSILON;
+        let over_point = point + normal_vector * bias;
         Computations {
             time: self.t,
             object: *self.object,
@@ -54,6 +84,7 @@ impl Intersection<'_> {
             normal_vector,
             inside,
             over_point,
+            time_of_ray: ray.time,
         }
     }
 }
@@ -160,6 +191,17 @@ mod tests {
         assert_eq!(computations.eye_vector, Vector::new(0.0, 0.0, -1.0));
     }
 
+    #[test]
+    fn precomputing_the_normal_of_a_moving_object() {
+        let mut shape = Object::Sphere(Sphere::new());
+        shape.set_motion(Matrix4::translate(2.0, 0.0, 0.0));
+        let ray = Ray::at_time(Point::new(1.0, 0.0, -5.0), Vector::new(0.0, 0.0, 1.0), 0.5);
+        let intersection = Intersection::new(4.0, &shape);
+        let computations = intersection.prepare_computations(ray);
+        assert_eq!(computations.point, Point::new(1.0, 0.0, -1.0));
+        assert_eq!(computations.normal_vector, Vector::new(0.0, 0.0, -1.0));
+    }
+
     #[test]
     fn precompute_hit_when_intersection_occurs_on_outside() {
         let ray = Ray::new(Point::new(0.0, 0.0, -5.0), Vector::new(0.0, 0.0, 1.0));
@@ -191,4 +233,13 @@ mod tests {
         let computations = intersection.prepare_computations(ray);
         assert!(computations.over_point.z < -EPSILON / 2.0);
     }
+
+    #[test]
+    fn hit_offset_uses_the_given_bias() {
+        let ray = Ray::new(Point::new(0.0, 0.0, -5.0), Vector::new(0.0, 0.0, 1.0));
+        let shape = Object::Sphere(Sphere::new());
+        let intersection = Intersection::new(4.0, &shape);
+        let computations = intersection.prepare_computations_with_bias(ray, 0.01);
+        assert_eq!(computations.over_point, Point::new(0.0, 0.0, -1.01));
+    }
 }
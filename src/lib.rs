@@ -1,15 +1,25 @@
+pub mod bounds;
 pub mod camera;
 pub mod canvas;
 pub mod color;
+pub mod disk;
 pub mod fire_projectiles;
 pub mod floats;
 pub mod intersections;
 pub mod lights;
 pub mod materials;
 pub mod matrices;
+pub mod obj_loader;
 pub mod objects;
+pub mod prelude;
+pub mod quad;
 pub mod rays;
+pub mod rng;
 pub mod sphere;
+pub mod stats;
+pub mod torus;
 pub mod transformations;
+pub mod triangle;
 pub mod tuples;
+pub mod uv;
 pub mod world;
@@ -1,10 +1,13 @@
 use crate::color::Color;
-use crate::tuples::Point;
+use crate::rng::Rng;
+use crate::tuples::{Point, Vector};
 
 #[derive(Clone, Copy)]
 pub struct PointLight {
     pub position: Point,
     pub intensity: Color,
+    // (constant, linear, quadratic) falloff with distance. (1, 0, 0) means no falloff.
+    pub attenuation: (f64, f64, f64),
 }
 
 impl PointLight {
@@ -12,15 +15,279 @@ impl PointLight {
         PointLight {
             position,
             intensity,
+            attenuation: (1.0, 0.0, 0.0),
         }
     }
+
+    pub fn with_attenuation(mut self, constant: f64, linear: f64, quadratic: f64) -> PointLight {
+        self.attenuation = (constant, linear, quadratic);
+        self
+    }
+
+    // Scale applied to the light's diffuse and specular contribution at point
+    pub fn attenuation_at(&self, point: Point) -> f64 {
+        let (constant, linear, quadratic) = self.attenuation;
+        let distance = point.distance(&self.position);
+        1.0 / (constant + linear * distance + quadratic * distance * distance)
+    }
+}
+
+// A rectangular light made of usteps * vsteps cells, sampled once per cell for soft shadows
+#[derive(Clone, Copy)]
+pub struct AreaLight {
+    pub corner: Point,
+    // uvec and vvec are the size of a single cell, not the whole edge
+    pub uvec: Vector,
+    pub usteps: u32,
+    pub vvec: Vector,
+    pub vsteps: u32,
+    pub intensity: Color,
+    pub position: Point,
+    // When false, samples are taken from the center of each cell
+    pub jitter: bool,
+}
+
+impl AreaLight {
+    pub fn new(
+        corner: Point,
+        full_uvec: Vector,
+        usteps: u32,
+        full_vvec: Vector,
+        vsteps: u32,
+        intensity: Color,
+    ) -> AreaLight {
+        // A light needs at least one cell to be sampled at all
+        let usteps = usteps.max(1);
+        let vsteps = vsteps.max(1);
+        AreaLight {
+            corner,
+            uvec: full_uvec / usteps as f64,
+            usteps,
+            vvec: full_vvec / vsteps as f64,
+            vsteps,
+            intensity,
+            position: corner + full_uvec / 2.0 + full_vvec / 2.0,
+            jitter: false,
+        }
+    }
+
+    pub fn samples(&self) -> u32 {
+        self.usteps * self.vsteps
+    }
+
+    // The center of cell (u, v)
+    pub fn point_on_light(&self, u: u32, v: u32) -> Point {
+        self.point_in_cell(u, v, 0.5, 0.5)
+    }
+
+    // One sample per cell for shading point. With jitter, each sample is at a random spot in
+    // its cell that changes from one shaded point to the next, so the cells don't show up as
+    // bands in soft shadows. The same point always gets the same samples.
+    pub fn points_seen_from(&self, point: Point) -> Vec<Point> {
+        self.points_from(&mut Rng::for_point(point))
+    }
+
+    // One sample per cell, jittered within the cell by rng when jitter is on
+    pub fn points_from(&self, rng: &mut Rng) -> Vec<Point> {
+        let mut points = Vec::with_capacity(self.samples() as usize);
+        for v in 0..self.vsteps {
+            for u in 0..self.usteps {
+                points.push(if self.jitter {
+                    self.point_in_cell(u, v, rng.next_f64(), rng.next_f64())
+                } else {
+                    self.point_on_light(u, v)
+                });
+            }
+        }
+        points
+    }
+
+    // du and dv are where in the cell the point is, from 0.0 to 1.0
+    fn point_in_cell(&self, u: u32, v: u32, du: f64, dv: f64) -> Point {
+        self.corner + self.uvec * (u as f64 + du) + self.vvec * (v as f64 + dv)
+    }
+}
+
+// A point light that only shines within a cone. Angles are in radians, measured from direction.
+#[derive(Clone, Copy)]
+pub struct SpotLight {
+    pub position: Point,
+    pub direction: Vector,
+    pub intensity: Color,
+    // Fully lit inside inner_angle, fading to unlit at outer_angle
+    pub inner_angle: f64,
+    pub outer_angle: f64,
+}
+
+impl SpotLight {
+    pub fn new(
+        position: Point,
+        direction: Vector,
+        intensity: Color,
+        inner_angle: f64,
+        outer_angle: f64,
+    ) -> SpotLight {
+        SpotLight {
+            position,
+            direction: direction.normalize(),
+            intensity,
+            inner_angle,
+            outer_angle,
+        }
+    }
+
+    // How much of the light reaches point based on its angle from the cone's axis
+    pub fn falloff(&self, point: Point) -> f64 {
+        let to_point = (point - self.position).normalize();
+        let cos_angle = to_point.dot(&self.direction);
+        let cos_inner = self.inner_angle.cos();
+        let cos_outer = self.outer_angle.cos();
+        if cos_angle >= cos_inner {
+            1.0
+        } else if cos_angle <= cos_outer {
+            0.0
+        } else {
+            // Smoothstep between the two cone edges
+            let t = (cos_angle - cos_outer) / (cos_inner - cos_outer);
+            t * t * (3.0 - 2.0 * t)
+        }
+    }
+}
+
+// A light so far away that its rays are parallel, like the sun. direction is the way the
+// light travels, so a sun overhead points straight down.
+#[derive(Clone, Copy)]
+pub struct DirectionalLight {
+    pub direction: Vector,
+    pub intensity: Color,
+}
+
+impl DirectionalLight {
+    pub fn new(direction: Vector, intensity: Color) -> DirectionalLight {
+        DirectionalLight {
+            direction: direction.normalize(),
+            intensity,
+        }
+    }
+}
+
+#[derive(Clone, Copy)]
+pub enum Light {
+    Point(PointLight),
+    Area(AreaLight),
+    Spot(SpotLight),
+    Directional(DirectionalLight),
+}
+
+impl Light {
+    pub fn intensity(&self) -> Color {
+        match *self {
+            Light::Point(ref l) => l.intensity,
+            Light::Area(ref l) => l.intensity,
+            Light::Spot(ref l) => l.intensity,
+            Light::Directional(ref l) => l.intensity,
+        }
+    }
+
+    // Directional lights are infinitely far away, so they have no position
+    pub fn position(&self) -> Option<Point> {
+        match *self {
+            Light::Point(ref l) => Some(l.position),
+            Light::Area(ref l) => Some(l.position),
+            Light::Spot(ref l) => Some(l.position),
+            Light::Directional(_) => None,
+        }
+    }
+
+    // Fraction of the light aimed at point, before any shadowing
+    pub fn falloff(&self, point: Point) -> f64 {
+        match *self {
+            Light::Spot(ref l) => l.falloff(point),
+            _ => 1.0,
+        }
+    }
+
+    // How much the light has faded with distance by the time it reaches point
+    pub fn attenuation_at(&self, point: Point) -> f64 {
+        match *self {
+            Light::Point(ref l) => l.attenuation_at(point),
+            _ => 1.0,
+        }
+    }
+
+    // Every point on the light that shading and shadow tests at point should sample. A point
+    // light is a single sample, and a directional light has none since it has no position.
+    pub fn sample_points(&self, point: Point) -> Vec<Point> {
+        match *self {
+            Light::Point(ref l) => vec![l.position],
+            Light::Spot(ref l) => vec![l.position],
+            Light::Area(ref l) => l.points_seen_from(point),
+            Light::Directional(_) => Vec::new(),
+        }
+    }
+
+    // Like sample_points, but a jittered area light draws its samples from rng, e.g. one
+    // seeded for the pixel being rendered
+    pub fn sample_points_from(&self, rng: &mut Rng) -> Vec<Point> {
+        match *self {
+            Light::Point(ref l) => vec![l.position],
+            Light::Spot(ref l) => vec![l.position],
+            Light::Area(ref l) => l.points_from(rng),
+            Light::Directional(_) => Vec::new(),
+        }
+    }
+
+    // The direction from point towards each sample of the light, and how far away it is
+    pub fn samples_from(&self, point: Point) -> Vec<(Vector, f64)> {
+        self.directions_to(point, &self.sample_points(point))
+    }
+
+    // The direction from point towards each of samples, and how far away it is. Directional
+    // lights have no samples, and are always in the same direction, infinitely far away.
+    pub fn directions_to(&self, point: Point, samples: &[Point]) -> Vec<(Vector, f64)> {
+        match *self {
+            Light::Directional(ref l) => vec![(-l.direction, f64::INFINITY)],
+            _ => samples
+                .iter()
+                .map(|sample| {
+                    let to_light = *sample - point;
+                    (to_light.normalize(), to_light.magnitude())
+                })
+                .collect(),
+        }
+    }
+}
+
+impl From<PointLight> for Light {
+    fn from(light: PointLight) -> Light {
+        Light::Point(light)
+    }
+}
+
+impl From<AreaLight> for Light {
+    fn from(light: AreaLight) -> Light {
+        Light::Area(light)
+    }
+}
+
+impl From<SpotLight> for Light {
+    fn from(light: SpotLight) -> Light {
+        Light::Spot(light)
+    }
+}
+
+impl From<DirectionalLight> for Light {
+    fn from(light: DirectionalLight) -> Light {
+        Light::Directional(light)
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use crate::color::Color;
-    use crate::lights::PointLight;
-    use crate::tuples::{Point, Tuple};
+    use crate::lights::{AreaLight, DirectionalLight, Light, PointLight, SpotLight};
+    use crate::tuples::{Point, Tuple, Vector};
+    use std::f64::consts::PI;
 
     #[test]
     fn point_light_has_position_and_intensity() {
@@ -30,4 +297,186 @@ mod tests {
         assert_eq!(light.position, position);
         assert_eq!(light.intensity, intensity);
     }
+
+    #[test]
+    fn creating_an_area_light() {
+        let corner = Point::new(0.0, 0.0, 0.0);
+        let v1 = Vector::new(2.0, 0.0, 0.0);
+        let v2 = Vector::new(0.0, 0.0, 1.0);
+        let light = AreaLight::new(corner, v1, 4, v2, 2, Color::white());
+        assert_eq!(light.corner, corner);
+        assert_eq!(light.uvec, Vector::new(0.5, 0.0, 0.0));
+        assert_eq!(light.usteps, 4);
+        assert_eq!(light.vvec, Vector::new(0.0, 0.0, 0.5));
+        assert_eq!(light.vsteps, 2);
+        assert_eq!(light.samples(), 8);
+        assert_eq!(light.position, Point::new(1.0, 0.0, 0.5));
+    }
+
+    #[test]
+    fn an_area_light_has_at_least_one_cell() {
+        let corner = Point::new(0.0, 0.0, 0.0);
+        let v1 = Vector::new(2.0, 0.0, 0.0);
+        let v2 = Vector::new(0.0, 0.0, 1.0);
+        let light = AreaLight::new(corner, v1, 0, v2, 0, Color::white());
+        assert_eq!(light.samples(), 1);
+        assert_eq!(light.uvec, v1);
+        assert_eq!(light.vvec, v2);
+        assert_eq!(light.point_on_light(0, 0), Point::new(1.0, 0.0, 0.5));
+    }
+
+    #[test]
+    fn finding_a_single_point_on_an_area_light() {
+        let corner = Point::new(0.0, 0.0, 0.0);
+        let v1 = Vector::new(2.0, 0.0, 0.0);
+        let v2 = Vector::new(0.0, 0.0, 1.0);
+        let light = AreaLight::new(corner, v1, 4, v2, 2, Color::white());
+        let cases = [
+            (0, 0, Point::new(0.25, 0.0, 0.25)),
+            (1, 0, Point::new(0.75, 0.0, 0.25)),
+            (0, 1, Point::new(0.25, 0.0, 0.75)),
+            (2, 0, Point::new(1.25, 0.0, 0.25)),
+            (3, 1, Point::new(1.75, 0.0, 0.75)),
+        ];
+        for (u, v, expected) in cases {
+            assert_eq!(light.point_on_light(u, v), expected);
+        }
+    }
+
+    #[test]
+    fn jittered_points_stay_within_their_cell() {
+        let corner = Point::new(0.0, 0.0, 0.0);
+        let v1 = Vector::new(2.0, 0.0, 0.0);
+        let v2 = Vector::new(0.0, 0.0, 1.0);
+        let mut light = AreaLight::new(corner, v1, 4, v2, 2, Color::white());
+        light.jitter = true;
+        let points = light.points_seen_from(Point::new(0.0, 5.0, 0.0));
+        assert_eq!(points.len(), 8);
+        for (i, point) in points.iter().enumerate() {
+            let (u, v) = (i % 4, i / 4);
+            assert!(point.x >= u as f64 * 0.5 && point.x < (u + 1) as f64 * 0.5);
+            assert!(point.z >= v as f64 * 0.5 && point.z < (v + 1) as f64 * 0.5);
+        }
+    }
+
+    #[test]
+    fn jitter_changes_from_one_shaded_point_to_the_next() {
+        let corner = Point::new(0.0, 0.0, 0.0);
+        let v1 = Vector::new(2.0, 0.0, 0.0);
+        let v2 = Vector::new(0.0, 0.0, 1.0);
+        let mut light = AreaLight::new(corner, v1, 4, v2, 2, Color::white());
+        light.jitter = true;
+        let a = Point::new(0.0, 5.0, 0.0);
+        let b = Point::new(0.1, 5.0, 0.0);
+        assert_eq!(light.points_seen_from(a), light.points_seen_from(a));
+        assert_ne!(light.points_seen_from(a), light.points_seen_from(b));
+
+        light.jitter = false;
+        assert_eq!(light.points_seen_from(a), light.points_seen_from(b));
+    }
+
+    #[test]
+    fn point_light_is_a_single_sample() {
+        let light = Light::from(PointLight::new(Point::new(1.0, 2.0, 3.0), Color::white()));
+        assert_eq!(
+            light.sample_points(Point::new(0.0, 0.0, 0.0)),
+            vec![Point::new(1.0, 2.0, 3.0)]
+        );
+    }
+
+    #[test]
+    fn area_light_samples_every_cell() {
+        let corner = Point::new(0.0, 0.0, 0.0);
+        let v1 = Vector::new(2.0, 0.0, 0.0);
+        let v2 = Vector::new(0.0, 0.0, 1.0);
+        let light = Light::from(AreaLight::new(corner, v1, 4, v2, 2, Color::white()));
+        assert_eq!(light.sample_points(Point::new(0.0, 0.0, 0.0)).len(), 8);
+    }
+
+    fn spotlight_pointing_down() -> SpotLight {
+        SpotLight::new(
+            Point::new(0.0, 10.0, 0.0),
+            Vector::new(0.0, -2.0, 0.0),
+            Color::white(),
+            PI / 12.0,
+            PI / 6.0,
+        )
+    }
+
+    #[test]
+    fn spotlight_normalizes_its_direction() {
+        let light = spotlight_pointing_down();
+        assert_eq!(light.direction, Vector::new(0.0, -1.0, 0.0));
+    }
+
+    #[test]
+    fn spotlight_is_full_strength_inside_inner_angle() {
+        let light = spotlight_pointing_down();
+        assert_eq!(light.falloff(Point::new(0.0, 0.0, 0.0)), 1.0);
+        assert_eq!(light.falloff(Point::new(1.0, 0.0, 0.0)), 1.0);
+    }
+
+    #[test]
+    fn spotlight_is_dark_outside_outer_angle() {
+        let light = spotlight_pointing_down();
+        assert_eq!(light.falloff(Point::new(10.0, 0.0, 0.0)), 0.0);
+        assert_eq!(light.falloff(Point::new(0.0, 20.0, 0.0)), 0.0);
+    }
+
+    #[test]
+    fn spotlight_fades_between_inner_and_outer_angle() {
+        let light = spotlight_pointing_down();
+        // 22.5 degrees is halfway between the 15 and 30 degree edges
+        let point = Point::new((PI / 8.0).tan() * 10.0, 0.0, 0.0);
+        let falloff = light.falloff(point);
+        assert!(falloff > 0.0 && falloff < 1.0);
+    }
+
+    #[test]
+    fn only_spotlights_have_falloff() {
+        let light = Light::from(PointLight::new(Point::new(0.0, 10.0, 0.0), Color::white()));
+        assert_eq!(light.falloff(Point::new(100.0, 0.0, 0.0)), 1.0);
+        let light = Light::from(spotlight_pointing_down());
+        assert_eq!(light.falloff(Point::new(100.0, 0.0, 0.0)), 0.0);
+    }
+
+    #[test]
+    fn point_lights_do_not_fade_by_default() {
+        let light = PointLight::new(Point::new(0.0, 0.0, 0.0), Color::white());
+        assert_eq!(light.attenuation, (1.0, 0.0, 0.0));
+        assert_eq!(light.attenuation_at(Point::new(0.0, 100.0, 0.0)), 1.0);
+    }
+
+    #[test]
+    fn attenuated_point_light_fades_with_distance() {
+        let light = Light::from(
+            PointLight::new(Point::new(0.0, 0.0, 0.0), Color::white())
+                .with_attenuation(1.0, 0.5, 0.25),
+        );
+        assert_eq!(light.attenuation_at(Point::new(0.0, 0.0, 0.0)), 1.0);
+        assert_eq!(light.attenuation_at(Point::new(2.0, 0.0, 0.0)), 1.0 / 3.0);
+        assert_eq!(light.attenuation_at(Point::new(0.0, 0.0, -4.0)), 1.0 / 7.0);
+    }
+
+    #[test]
+    fn directional_light_has_one_sample_from_every_point() {
+        let light = Light::from(DirectionalLight::new(
+            Vector::new(0.0, -2.0, 0.0),
+            Color::white(),
+        ));
+        assert!(light.position().is_none());
+        for point in [Point::new(0.0, 0.0, 0.0), Point::new(100.0, -5.0, 3.0)] {
+            let samples = light.samples_from(point);
+            assert_eq!(samples.len(), 1);
+            assert_eq!(samples[0].0, Vector::new(0.0, 1.0, 0.0));
+            assert_eq!(samples[0].1, f64::INFINITY);
+        }
+    }
+
+    #[test]
+    fn samples_from_a_point_light() {
+        let light = Light::from(PointLight::new(Point::new(0.0, 3.0, 4.0), Color::white()));
+        let samples = light.samples_from(Point::new(0.0, 0.0, 0.0));
+        assert_eq!(samples, vec![(Vector::new(0.0, 0.6, 0.8), 5.0)]);
+    }
 }
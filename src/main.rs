@@ -1,17 +1,6 @@
-use raytracer::camera::Camera;
-use raytracer::canvas::Canvas;
-use raytracer::color::Color;
 use raytracer::fire_projectiles::{tick, Environment, Projectile};
 use raytracer::intersections::Intersection;
-use raytracer::lights::PointLight;
-use raytracer::materials::Material;
-use raytracer::matrices::Matrix4;
-use raytracer::objects::{Intersectable, Object};
-use raytracer::rays::Ray;
-use raytracer::sphere::Sphere;
-use raytracer::transformations::view_transform;
-use raytracer::tuples::{Point, Tuple, Vector};
-use raytracer::world::World;
+use raytracer::prelude::*;
 use std::f64::consts::PI;
 
 #[allow(dead_code)]
@@ -33,15 +22,22 @@ fn draw_chapter_2_arc() {
 
     let arc_color = Color::new(1.0, 0.0, 0.0);
 
+    // Join each position to the next, so the arc has no gaps at high speed
     while (projectile.position.y > 0.0) && (projectile.position.x < canvas_width as f64) {
-        let x = projectile.position.x.round() as u32;
-        let y = projectile.position.y.round() as u32;
-        canvas.write_pixel(x, canvas_height - y, &arc_color);
-        projectile = tick(&environment, projectile);
+        let from = projectile.position;
+        let next = tick(&environment, projectile);
+        canvas.draw_line(
+            from.x.round() as i64,
+            canvas_height as i64 - from.y.round() as i64,
+            next.position.x.round() as i64,
+            canvas_height as i64 - next.position.y.round() as i64,
+            &arc_color,
+        );
+        projectile = next;
     }
 
-    let _ = canvas.to_ppm("outputs/chapter_2_arc.ppm");
-    let _ = canvas.to_jpeg("outputs/chapter_2_arc.jpg");
+    let _ = canvas.save("outputs/chapter_2_arc.ppm");
+    let _ = canvas.save("outputs/chapter_2_arc.jpg");
 }
 
 #[allow(dead_code)]
@@ -56,6 +52,7 @@ fn draw_chapter_4_clock() {
     let center = Point::new(0.0, 0.0, 0.0);
     let twelve = Point::new(0.0, 0.0, 1.0);
 
+    let mut hours = Vec::new();
     for i in 0..12 {
         let r = Matrix4::rotate_y((i as f64) * PI / 6.0);
         let point = r * twelve;
@@ -67,11 +64,18 @@ fn draw_chapter_4_clock() {
         // Convert points to canvas coordinates
         let x = (x * clock_width / 2.0) + (canvas_width as f64 / 2.0);
         let z = (z * clock_height / 2.0) + (canvas_height as f64 / 2.0);
-        canvas.write_pixel(x.round() as u32, z.round() as u32, &color);
+        hours.push((x.round() as i64, z.round() as i64));
     }
 
-    let _ = canvas.to_ppm("outputs/chapter_4_clock.ppm");
-    let _ = canvas.to_jpeg("outputs/chapter_4_clock.jpg");
+    // Mark each hour, and connect them around the clock face
+    for (i, &(x, y)) in hours.iter().enumerate() {
+        let (next_x, next_y) = hours[(i + 1) % hours.len()];
+        canvas.draw_circle(x, y, 5, &color);
+        canvas.draw_line(x, y, next_x, next_y, &color);
+    }
+
+    let _ = canvas.save("outputs/chapter_4_clock.ppm");
+    let _ = canvas.save("outputs/chapter_4_clock.jpg");
 }
 
 #[allow(dead_code)]
@@ -99,8 +103,8 @@ fn draw_chapter_5_circle() {
             }
         }
     }
-    let _ = canvas.to_ppm("outputs/chapter_5_circle.ppm");
-    let _ = canvas.to_jpeg("outputs/chapter_5_circle.jpg");
+    let _ = canvas.save("outputs/chapter_5_circle.ppm");
+    let _ = canvas.save("outputs/chapter_5_circle.jpg");
 }
 
 #[allow(dead_code)]
@@ -122,7 +126,7 @@ fn draw_chapter_6_sphere() {
     sphere.set_material(material);
     let light_position = Point::new(-10.0, 10.0, -10.0);
     let light_color = Color::white();
-    let light = PointLight::new(light_position, light_color);
+    let light = Light::from(PointLight::new(light_position, light_color));
 
     for y in 0..canvas_height - 1 {
         let world_y = half - pixel_size * (y as f64);
@@ -138,13 +142,13 @@ fn draw_chapter_6_sphere() {
                 let color = hit
                     .object
                     .material()
-                    .lighting(light, point, eye, normal, false);
+                    .lighting(light, point, eye, normal, 1.0);
                 canvas.write_pixel(x, y, &color);
             }
         }
     }
-    let _ = canvas.to_ppm("outputs/chapter_6_sphere.ppm");
-    let _ = canvas.to_jpeg("outputs/chapter_6_sphere.jpg");
+    let _ = canvas.save("outputs/chapter_6_sphere.ppm");
+    let _ = canvas.save("outputs/chapter_6_sphere.jpg");
 }
 
 #[allow(dead_code)]
@@ -157,27 +161,28 @@ fn draw_chapter_7_and_8_world() {
     };
 
     let floor = Object::Sphere(Sphere {
-        transformation: Matrix4::scale(10.0, 0.01, 10.0),
         material: floor_material,
-        ..Default::default()
+        ..Sphere::with_transform(Matrix4::scale(10.0, 0.01, 10.0))
     });
 
     let left_wall = Object::Sphere(Sphere {
         material: floor_material,
-        transformation: Matrix4::translate(0.0, 0.0, 5.0)
-            * Matrix4::rotate_y(-PI / 4.0)
-            * Matrix4::rotate_x(PI / 2.0)
-            * Matrix4::scale(10.0, 0.01, 10.0),
-        ..Default::default()
+        ..Sphere::with_transform(
+            Matrix4::translate(0.0, 0.0, 5.0)
+                * Matrix4::rotate_y(-PI / 4.0)
+                * Matrix4::rotate_x(PI / 2.0)
+                * Matrix4::scale(10.0, 0.01, 10.0),
+        )
     });
 
     let right_wall = Object::Sphere(Sphere {
         material: floor_material,
-        transformation: Matrix4::translate(0.0, 0.0, 5.0)
-            * Matrix4::rotate_y(PI / 4.0)
-            * Matrix4::rotate_x(PI / 2.0)
-            * Matrix4::scale(10.0, 0.01, 10.0),
-        ..Default::default()
+        ..Sphere::with_transform(
+            Matrix4::translate(0.0, 0.0, 5.0)
+                * Matrix4::rotate_y(PI / 4.0)
+                * Matrix4::rotate_x(PI / 2.0)
+                * Matrix4::scale(10.0, 0.01, 10.0),
+        )
     });
 
     let middle_material = Material {
@@ -189,8 +194,7 @@ fn draw_chapter_7_and_8_world() {
 
     let middle = Object::Sphere(Sphere {
         material: middle_material,
-        transformation: Matrix4::translate(-0.5, 1.0, 0.5),
-        ..Default::default()
+        ..Sphere::with_transform(Matrix4::translate(-0.5, 1.0, 0.5))
     });
 
     let right_material = Material {
@@ -202,8 +206,7 @@ fn draw_chapter_7_and_8_world() {
 
     let right = Object::Sphere(Sphere {
         material: right_material,
-        transformation: Matrix4::translate(1.5, 0.5, -0.5) * Matrix4::scale(0.5, 0.5, 0.5),
-        ..Default::default()
+        ..Sphere::with_transform(Matrix4::translate(1.5, 0.5, -0.5) * Matrix4::scale(0.5, 0.5, 0.5))
     });
 
     let left_material = Material {
@@ -215,28 +218,30 @@ fn draw_chapter_7_and_8_world() {
 
     let left = Object::Sphere(Sphere {
         material: left_material,
-        transformation: Matrix4::translate(-1.5, 0.33, -0.75) * Matrix4::scale(0.33, 0.33, 0.33),
-        ..Default::default()
+        ..Sphere::with_transform(
+            Matrix4::translate(-1.5, 0.33, -0.75) * Matrix4::scale(0.33, 0.33, 0.33),
+        )
     });
 
     let world = World {
         objects: vec![floor, left_wall, right_wall, middle, right, left],
-        light_source: Some(PointLight::new(
+        lights: vec![Light::from(PointLight::new(
             Point::new(-10.0, 10.0, -10.0),
             Color::white(),
-        )),
+        ))],
+        ..Default::default()
     };
 
     let mut camera = Camera::new(1000, 500, PI / 3.0);
-    camera.transform = view_transform(
+    camera.set_transform(view_transform(
         Point::new(0.0, 1.5, -5.0),
         Point::new(0.0, 1.0, 0.0),
         Vector::new(0.0, 1.0, 0.0),
-    );
+    ));
 
     let canvas = camera.render(world);
-    let _ = canvas.to_ppm("outputs/chapter_7_and_8_world.ppm");
-    let _ = canvas.to_jpeg("outputs/chapter_7_and_8_world.jpg");
+    let _ = canvas.save("outputs/chapter_7_and_8_world.ppm");
+    let _ = canvas.save("outputs/chapter_7_and_8_world.jpg");
 }
 
 fn main() {
@@ -1,6 +1,6 @@
 use crate::color::Color;
 use crate::floats::float_equal;
-use crate::lights::PointLight;
+use crate::lights::Light;
 use crate::tuples::{Point, Vector};
 
 #[derive(Debug, Copy, Clone)]
@@ -10,6 +10,12 @@ pub struct Material {
     pub diffuse: f64,
     pub specular: f64,
     pub shininess: f64,
+    // Light given off by the surface itself, seen even when nothing lights it
+    pub emission: Color,
+    // Turn off for objects like glass or water that shouldn't darken what's behind them
+    pub casts_shadow: bool,
+    // Lets thin surfaces like disks and quads be lit from behind as well as in front
+    pub two_sided: bool,
 }
 
 impl Material {
@@ -29,51 +35,82 @@ impl Material {
             diffuse,
             specular,
             shininess,
+            emission: Color::black(),
+            casts_shadow: true,
+            two_sided: false,
         }
     }
 
+    // The part of lighting that doesn't depend on where the light is
+    pub fn ambient_color(&self, light: &Light) -> Color {
+        self.color * light.intensity() * self.ambient
+    }
+
+    // intensity is the fraction of the light that reaches the point, from 0.0 (fully in
+    // shadow) to 1.0 (fully lit)
     pub fn lighting(
         &self,
-        light: PointLight,
+        light: Light,
         point: Point,
         eye_vector: Vector,
         normal_vector: Vector,
-        in_shadow: bool,
+        intensity: f64,
     ) -> Color {
-        // Combine surface color with the light's color/intensity
-        let effective_color = self.color * light.intensity;
+        let samples = light.sample_points(point);
+        self.lighting_with_samples(light, point, eye_vector, normal_vector, intensity, &samples)
+    }
 
-        // Find the direction to the light source
-        let light_vector = (light.position - point).normalize();
+    // Like lighting, but with the points on the light to sample already chosen, so shading
+    // can use the same ones its shadow rays were cast towards
+    pub fn lighting_with_samples(
+        &self,
+        light: Light,
+        point: Point,
+        eye_vector: Vector,
+        normal_vector: Vector,
+        intensity: f64,
+        samples: &[Point],
+    ) -> Color {
+        // Combine surface color with the light's color/intensity
+        let effective_color = self.color * light.intensity();
 
         // Compute the ambient contribution
-        let ambient = effective_color * self.ambient;
-
-        // light_dot_normal represents the cosine of the angle between the
-        // light vector and the normal vector. A negative number means the
-        // light is on the other side of the surface.
-        let light_dot_normal = light_vector.dot(&normal_vector);
+        let ambient = self.ambient_color(&light);
 
-        let mut diffuse = Color::black();
-        let mut specular = Color::black();
+        let samples = light.directions_to(point, samples);
+        let mut sum = Color::black();
+        for (light_vector, _) in samples.iter() {
+            // light_dot_normal represents the cosine of the angle between the
+            // light vector and the normal vector. A negative number means the
+            // light is on the other side of the surface.
+            let mut normal_vector = normal_vector;
+            let mut light_dot_normal = light_vector.dot(&normal_vector);
+            if light_dot_normal < 0.0 && self.two_sided {
+                // Light the back of the surface as if it were the front
+                normal_vector = -normal_vector;
+                light_dot_normal = -light_dot_normal;
+            }
+            if light_dot_normal < 0.0 {
+                continue;
+            }
 
-        if light_dot_normal >= 0.0 && !in_shadow {
             // Compute diffuse
-            diffuse = effective_color * self.diffuse * light_dot_normal;
+            sum = sum + effective_color * self.diffuse * light_dot_normal;
 
             // reflect_dot_eye represents the cosine of the angle between the
             // reflection vector and the eye vector. A negative number means the
             // light reflects away from the eye.
-            let reflect_vector = (-light_vector).reflect(&normal_vector);
+            let reflect_vector = (-*light_vector).reflect(&normal_vector);
             let reflect_dot_eye = reflect_vector.dot(&eye_vector);
             if reflect_dot_eye > 0.0 {
                 // Compute specular
                 let factor = reflect_dot_eye.powf(self.shininess);
-                specular = light.intensity * self.specular * factor;
+                sum = sum + light.intensity() * self.specular * factor;
             }
         }
 
-        ambient + diffuse + specular
+        let attenuation = light.attenuation_at(point);
+        ambient + sum * (intensity * attenuation / samples.len().max(1) as f64)
     }
 }
 
@@ -90,15 +127,21 @@ impl PartialEq for Material {
             && float_equal(self.ambient, other.ambient)
             && float_equal(self.diffuse, other.diffuse)
             && float_equal(self.specular, other.specular)
+            && self.emission == other.emission
+            && self.casts_shadow == other.casts_shadow
+            && self.two_sided == other.two_sided
     }
 }
 
 #[cfg(test)]
 mod tests {
     use crate::color::Color;
-    use crate::lights::PointLight;
+    use crate::lights::{AreaLight, Light, PointLight};
     use crate::materials::Material;
+    use crate::objects::{Intersectable, Object};
+    use crate::sphere::Sphere;
     use crate::tuples::{Point, Tuple, Vector};
+    use std::f64::consts::FRAC_1_SQRT_2;
 
     #[test]
     fn default_material() {
@@ -108,6 +151,8 @@ mod tests {
         assert_eq!(material.diffuse, 0.9);
         assert_eq!(material.specular, 0.9);
         assert_eq!(material.shininess, 200.0);
+        assert_eq!(material.emission, Color::black());
+        assert!(material.casts_shadow);
     }
 
     #[test]
@@ -116,8 +161,8 @@ mod tests {
         let position = Point::new(0.0, 0.0, 0.0);
         let eye_vector = Vector::new(0.0, 0.0, -1.0);
         let normal_vector = Vector::new(0.0, 0.0, -1.0);
-        let light = PointLight::new(Point::new(0.0, 0.0, -10.0), Color::white());
-        let result = material.lighting(light, position, eye_vector, normal_vector, false);
+        let light = Light::from(PointLight::new(Point::new(0.0, 0.0, -10.0), Color::white()));
+        let result = material.lighting(light, position, eye_vector, normal_vector, 1.0);
         assert_eq!(result, Color::new(1.9, 1.9, 1.9));
     }
 
@@ -127,8 +172,8 @@ mod tests {
         let position = Point::new(0.0, 0.0, 0.0);
         let eye_vector = Vector::new(0.0, 2.0_f64.sqrt() / 2.0, -(2.0_f64.sqrt()) / 2.0);
         let normal_vector = Vector::new(0.0, 0.0, -1.0);
-        let light = PointLight::new(Point::new(0.0, 0.0, -10.0), Color::white());
-        let result = material.lighting(light, position, eye_vector, normal_vector, false);
+        let light = Light::from(PointLight::new(Point::new(0.0, 0.0, -10.0), Color::white()));
+        let result = material.lighting(light, position, eye_vector, normal_vector, 1.0);
         assert_eq!(result, Color::new(1.0, 1.0, 1.0));
     }
 
@@ -138,8 +183,11 @@ mod tests {
         let position = Point::new(0.0, 0.0, 0.0);
         let eye_vector = Vector::new(0.0, 0.0, -1.0);
         let normal_vector = Vector::new(0.0, 0.0, -1.0);
-        let light = PointLight::new(Point::new(0.0, 10.0, -10.0), Color::white());
-        let result = material.lighting(light, position, eye_vector, normal_vector, false);
+        let light = Light::from(PointLight::new(
+            Point::new(0.0, 10.0, -10.0),
+            Color::white(),
+        ));
+        let result = material.lighting(light, position, eye_vector, normal_vector, 1.0);
         assert_eq!(result, Color::new(0.7364, 0.7364, 0.7364));
     }
 
@@ -149,8 +197,11 @@ mod tests {
         let position = Point::new(0.0, 0.0, 0.0);
         let eye_vector = Vector::new(0.0, -(2.0_f64.sqrt()) / 2.0, -(2.0_f64.sqrt()) / 2.0);
         let normal_vector = Vector::new(0.0, 0.0, -1.0);
-        let light = PointLight::new(Point::new(0.0, 10.0, -10.0), Color::white());
-        let result = material.lighting(light, position, eye_vector, normal_vector, false);
+        let light = Light::from(PointLight::new(
+            Point::new(0.0, 10.0, -10.0),
+            Color::white(),
+        ));
+        let result = material.lighting(light, position, eye_vector, normal_vector, 1.0);
         assert_eq!(result, Color::new(1.6364, 1.6364, 1.6364));
     }
 
@@ -160,8 +211,8 @@ mod tests {
         let position = Point::new(0.0, 0.0, 0.0);
         let eye_vector = Vector::new(0.0, 0.0, -1.0);
         let normal_vector = Vector::new(0.0, 0.0, -1.0);
-        let light = PointLight::new(Point::new(0.0, 0.0, 10.0), Color::white());
-        let result = material.lighting(light, position, eye_vector, normal_vector, false);
+        let light = Light::from(PointLight::new(Point::new(0.0, 0.0, 10.0), Color::white()));
+        let result = material.lighting(light, position, eye_vector, normal_vector, 1.0);
         assert_eq!(result, Color::new(0.1, 0.1, 0.1));
     }
 
@@ -170,15 +221,109 @@ mod tests {
         let material = Material::default();
         let eye_vector = Vector::new(0.0, 0.0, -1.0);
         let normal_vector = Vector::new(0.0, 0.0, -1.0);
-        let light = PointLight::new(Point::new(0.0, 0.0, -10.0), Color::white());
-        let in_shadow = true;
+        let light = Light::from(PointLight::new(Point::new(0.0, 0.0, -10.0), Color::white()));
         let result = material.lighting(
             light,
             Point::new(0.0, 0.0, 0.0),
             eye_vector,
             normal_vector,
-            in_shadow,
+            0.0,
+        );
+        assert_eq!(result, Color::new(0.1, 0.1, 0.1));
+    }
+
+    #[test]
+    fn lighting_uses_light_intensity_to_attenuate_color() {
+        let light = Light::from(PointLight::new(Point::new(0.0, 0.0, -10.0), Color::white()));
+        let material = Material {
+            ambient: 0.1,
+            diffuse: 0.9,
+            specular: 0.0,
+            color: Color::white(),
+            ..Default::default()
+        };
+        let point = Point::new(0.0, 0.0, -1.0);
+        let eye_vector = Vector::new(0.0, 0.0, -1.0);
+        let normal_vector = Vector::new(0.0, 0.0, -1.0);
+        let cases = [
+            (1.0, Color::white()),
+            (0.5, Color::new(0.55, 0.55, 0.55)),
+            (0.0, Color::new(0.1, 0.1, 0.1)),
+        ];
+        for (intensity, expected) in cases {
+            let result = material.lighting(light, point, eye_vector, normal_vector, intensity);
+            assert_eq!(result, expected);
+        }
+    }
+
+    #[test]
+    fn lighting_samples_the_area_light() {
+        let corner = Point::new(-0.5, -0.5, -5.0);
+        let v1 = Vector::new(1.0, 0.0, 0.0);
+        let v2 = Vector::new(0.0, 1.0, 0.0);
+        let light = Light::from(AreaLight::new(corner, v1, 2, v2, 2, Color::white()));
+        let shape = Object::Sphere(Sphere {
+            material: Material {
+                ambient: 0.1,
+                diffuse: 0.9,
+                specular: 0.0,
+                color: Color::white(),
+                ..Default::default()
+            },
+            ..Default::default()
+        });
+        let eye = Point::new(0.0, 0.0, -5.0);
+        let cases = [
+            (
+                Point::new(0.0, 0.0, -1.0),
+                Color::new(0.9965, 0.9965, 0.9965),
+            ),
+            (
+                Point::new(0.0, FRAC_1_SQRT_2, -FRAC_1_SQRT_2),
+                Color::new(0.62318, 0.62318, 0.62318),
+            ),
+        ];
+        for (point, expected) in cases {
+            let eye_vector = (eye - point).normalize();
+            let normal_vector = Vector::new(point.x, point.y, point.z);
+            let result = shape
+                .material()
+                .lighting(light, point, eye_vector, normal_vector, 1.0);
+            assert_eq!(result, expected);
+        }
+    }
+
+    #[test]
+    fn lighting_with_an_attenuated_point_light() {
+        let material = Material::default();
+        let position = Point::new(0.0, 0.0, 0.0);
+        let eye_vector = Vector::new(0.0, 0.0, -1.0);
+        let normal_vector = Vector::new(0.0, 0.0, -1.0);
+        let light = Light::from(
+            PointLight::new(Point::new(0.0, 0.0, -10.0), Color::white())
+                .with_attenuation(1.0, 0.0, 0.01),
         );
+        // Diffuse and specular (0.9 + 0.9) are halved, ambient (0.1) is not
+        let result = material.lighting(light, position, eye_vector, normal_vector, 1.0);
+        assert_eq!(result, Color::new(1.0, 1.0, 1.0));
+    }
+
+    #[test]
+    fn lighting_a_two_sided_surface_from_behind() {
+        let position = Point::new(0.0, 0.0, 0.0);
+        let eye_vector = Vector::new(0.0, 0.0, -1.0);
+        let normal_vector = Vector::new(0.0, 0.0, -1.0);
+        let light = Light::from(PointLight::new(Point::new(0.0, 0.0, 10.0), Color::white()));
+
+        let one_sided = Material::default();
+        let result = one_sided.lighting(light, position, eye_vector, normal_vector, 1.0);
         assert_eq!(result, Color::new(0.1, 0.1, 0.1));
+
+        let two_sided = Material {
+            two_sided: true,
+            ..Default::default()
+        };
+        let result = two_sided.lighting(light, position, eye_vector, normal_vector, 1.0);
+        assert_eq!(result, Color::new(1.0, 1.0, 1.0));
     }
 }
@@ -1,5 +1,6 @@
-use crate::floats::float_equal;
+use crate::floats::{approx_equal, float_equal, EPSILON};
 use crate::tuples::{Point, Tuple, Vector};
+use std::fmt;
 use std::ops::Mul;
 
 // Most things rely on Matrix4, everything else is used by Matrix2 for things like cofactors.
@@ -80,6 +81,54 @@ impl Matrix4 {
         }
     }
 
+    // Rotates r radians around an arbitrary axis through the origin (Rodrigues' formula)
+    pub fn rotate_axis(axis: Vector, r: f64) -> Matrix4 {
+        let Vector { x, y, z } = axis.normalize();
+        let (sin, cos) = r.sin_cos();
+        let t = 1.0 - cos;
+        Matrix4 {
+            data: [
+                [
+                    t * x * x + cos,
+                    t * x * y - sin * z,
+                    t * x * z + sin * y,
+                    0.0,
+                ],
+                [
+                    t * x * y + sin * z,
+                    t * y * y + cos,
+                    t * y * z - sin * x,
+                    0.0,
+                ],
+                [
+                    t * x * z - sin * y,
+                    t * y * z + sin * x,
+                    t * z * z + cos,
+                    0.0,
+                ],
+                [0.0, 0.0, 0.0, 1.0],
+            ],
+        }
+    }
+
+    // Places an object at from, facing to: the object's -z axis points at to and its +y axis
+    // is as close to up as possible. With an up perpendicular to the view direction this is
+    // the inverse of view_transform, which moves the world in front of the camera instead.
+    pub fn orientation(from: Point, to: Point, up: Vector) -> Matrix4 {
+        let forward = (to - from).normalize();
+        // Normalized so the result is a pure rotation even when up isn't perpendicular
+        let left = forward.cross(&up).normalize();
+        let true_up = left.cross(&forward);
+        Matrix4 {
+            data: [
+                [left.x, true_up.x, -forward.x, from.x],
+                [left.y, true_up.y, -forward.y, from.y],
+                [left.z, true_up.z, -forward.z, from.z],
+                [0.0, 0.0, 0.0, 1.0],
+            ],
+        }
+    }
+
     pub fn shear(xy: f64, xz: f64, yx: f64, yz: f64, zx: f64, zy: f64) -> Matrix4 {
         Matrix4 {
             data: [
@@ -114,7 +163,9 @@ impl Matrix4 {
 
     #[allow(clippy::needless_range_loop)]
     pub fn inverse(&self) -> Option<Matrix4> {
-        if !self.is_invertible() {
+        // Computed once up front, since each determinant call expands every cofactor again
+        let determinant = self.determinant();
+        if self.is_singular(determinant) {
             return None;
         }
 
@@ -125,12 +176,38 @@ impl Matrix4 {
                 let c = self.cofactor(row, col);
 
                 // Transpose here by swapping row/col
-                data[col][row] = c / self.determinant();
+                data[col][row] = c / determinant;
             }
         }
         Some(Matrix4::new(data))
     }
 
+    // Like ==, but with a tolerance of epsilon rather than the fixed EPSILON
+    pub fn approx_eq(&self, other: &Matrix4, epsilon: f64) -> bool {
+        self.data
+            .iter()
+            .flatten()
+            .zip(other.data.iter().flatten())
+            .all(|(a, b)| approx_equal(*a, *b, epsilon))
+    }
+
+    pub fn try_inverse(&self) -> Result<Matrix4, MatrixError> {
+        self.inverse().ok_or(MatrixError::NotInvertible)
+    }
+
+    // Blends each element from self at t = 0.0 to other at t = 1.0. Fine for translation
+    // and scaling, but rotations in between won't be pure rotations.
+    pub fn lerp(&self, other: &Matrix4, t: f64) -> Matrix4 {
+        let mut matrix = Matrix4::default();
+        for row in 0..4 {
+            for col in 0..4 {
+                matrix.data[row][col] =
+                    self.data[row][col] + (other.data[row][col] - self.data[row][col]) * t;
+            }
+        }
+        matrix
+    }
+
     // Returns a new Matrix since we need the old values when calculating the output
     pub fn transpose(&self) -> Matrix4 {
         let mut matrix = Matrix4::default();
@@ -143,12 +220,31 @@ impl Matrix4 {
     }
 
     pub fn is_invertible(&self) -> bool {
-        !float_equal(self.determinant(), 0.0)
+        !self.is_singular(self.determinant())
+    }
+
+    // The determinant shrinks with the cube of a scale, so checking it against a fixed
+    // tolerance would call small but valid objects singular. Instead it's compared with the
+    // largest determinant rows of the same lengths could have (Hadamard's bound). Translation
+    // doesn't change an affine matrix's determinant, so only its 3x3 part counts there. A
+    // matrix with NaNs in it, like a view transform looking from a point to itself, is
+    // singular too.
+    fn is_singular(&self, determinant: f64) -> bool {
+        let size = if self.data[3] == [0.0, 0.0, 0.0, 1.0] {
+            3
+        } else {
+            4
+        };
+        let bound: f64 = self.data[..size]
+            .iter()
+            .map(|row| row[..size].iter().map(|x| x * x).sum::<f64>().sqrt())
+            .product();
+        determinant.is_nan() || determinant.abs() <= EPSILON * bound
     }
 
     pub fn cofactor(&self, row: usize, col: usize) -> f64 {
         let minor = self.minor(row, col);
-        if (row + col) % 2 == 0 {
+        if (row + col).is_multiple_of(2) {
             minor
         } else {
             -minor
@@ -160,6 +256,21 @@ impl Matrix4 {
     }
 }
 
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum MatrixError {
+    NotInvertible,
+}
+
+impl fmt::Display for MatrixError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            MatrixError::NotInvertible => write!(f, "Matrix is not invertible"),
+        }
+    }
+}
+
+impl std::error::Error for MatrixError {}
+
 impl Default for Matrix4 {
     fn default() -> Self {
         Matrix4::new([[0.0; 4]; 4])
@@ -220,14 +331,33 @@ impl Mul<Point> for Matrix4 {
 
 impl PartialEq for Matrix4 {
     fn eq(&self, other: &Self) -> bool {
-        for row in 0..4 {
-            for col in 0..4 {
-                if !float_equal(self.data[row][col], other.data[row][col]) {
-                    return false;
-                }
+        self.approx_eq(other, EPSILON)
+    }
+}
+
+// Prints one aligned row per line. Uses 5 decimal places unless a precision is given,
+// e.g. {:.2}
+impl fmt::Display for Matrix4 {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let precision = f.precision().unwrap_or(5);
+        let cells: Vec<String> = self
+            .data
+            .iter()
+            .flatten()
+            .map(|value| format!("{:.*}", precision, value))
+            .collect();
+        let width = cells.iter().map(|cell| cell.len()).max().unwrap_or(0);
+        for (row, chunk) in cells.chunks(4).enumerate() {
+            if row > 0 {
+                writeln!(f)?;
+            }
+            write!(f, "|")?;
+            for cell in chunk {
+                write!(f, " {:>width$}", cell, width = width)?;
             }
+            write!(f, " |")?;
         }
-        true
+        Ok(())
     }
 }
 
@@ -243,7 +373,7 @@ impl Matrix3 {
 
     pub fn cofactor(&self, row: usize, col: usize) -> f64 {
         let minor = self.minor(row, col);
-        if (row + col) % 2 == 0 {
+        if (row + col).is_multiple_of(2) {
             minor
         } else {
             -minor
@@ -331,10 +461,46 @@ impl PartialEq for Matrix2 {
 
 #[cfg(test)]
 mod tests {
-    use crate::matrices::{Matrix2, Matrix3, Matrix4};
+    use crate::matrices::{Matrix2, Matrix3, Matrix4, MatrixError};
+    use crate::transformations::view_transform;
     use crate::tuples::{Point, Tuple, Vector};
     use std::f64::consts::PI;
 
+    #[test]
+    fn displaying_a_4x4_matrix() {
+        let matrix = Matrix4::new([
+            [1.0, 2.0, 3.0, 4.0],
+            [5.5, 6.5, 7.5, 8.5],
+            [9.0, 10.0, 11.0, 12.0],
+            [-13.5, 14.5, 15.5, 16.5],
+        ]);
+        let expected = "\
+|   1.00   2.00   3.00   4.00 |
+|   5.50   6.50   7.50   8.50 |
+|   9.00  10.00  11.00  12.00 |
+| -13.50  14.50  15.50  16.50 |";
+        assert_eq!(format!("{:.2}", matrix), expected);
+        assert!(format!("{}", Matrix4::identity()).starts_with("| 1.00000 0.00000"));
+    }
+
+    #[test]
+    fn comparing_matrices_with_a_custom_tolerance() {
+        let combined = Matrix4::rotate_x(PI / 2.0);
+        let chained =
+            Matrix4::rotate_x(PI / 6.0) * Matrix4::rotate_x(PI / 6.0) * Matrix4::rotate_x(PI / 6.0);
+        assert!(combined.approx_eq(&chained, 1e-9));
+        assert!(!combined.approx_eq(&Matrix4::rotate_x(PI / 2.0 + 1e-6), 1e-9));
+    }
+
+    #[test]
+    fn interpolating_between_matrices() {
+        let from = Matrix4::translate(0.0, 0.0, 0.0);
+        let to = Matrix4::translate(4.0, -2.0, 0.0);
+        assert_eq!(from.lerp(&to, 0.0), from);
+        assert_eq!(from.lerp(&to, 0.25), Matrix4::translate(1.0, -0.5, 0.0));
+        assert_eq!(from.lerp(&to, 1.0), to);
+    }
+
     // First since this is the most used type of matrix.
     #[test]
     fn create_4x4_matrix() {
@@ -589,6 +755,22 @@ mod tests {
         ]);
         assert_eq!(matrix.determinant(), 0.0);
         assert!(!matrix.is_invertible());
+        assert!(matrix.inverse().is_none());
+        assert_eq!(matrix.try_inverse(), Err(MatrixError::NotInvertible));
+    }
+
+    #[test]
+    fn small_and_far_away_transforms_are_invertible() {
+        let small = Matrix4::scale(0.02, 0.02, 0.02);
+        assert!(small.is_invertible());
+        assert_eq!(small.inverse(), Some(Matrix4::scale(50.0, 50.0, 50.0)));
+
+        let far = Matrix4::translate(1.0e6, 0.0, 0.0) * Matrix4::scale(0.01, 0.01, 0.01);
+        assert!(far.is_invertible());
+
+        assert!(!Matrix4::scale(0.02, 0.0, 0.02).is_invertible());
+        assert!(!Matrix4::default().is_invertible());
+        assert!(!Matrix4::scale(f64::NAN, 1.0, 1.0).is_invertible());
     }
 
     #[test]
@@ -735,12 +917,40 @@ mod tests {
             [6.0, -2.0, 0.0, 5.0],
         ]);
 
-        let matrix_c = matrix_a.clone() * matrix_b.clone();
+        let matrix_c = matrix_a * matrix_b;
 
         assert_ne!(matrix_a, matrix_b);
         assert_eq!(matrix_a, matrix_c * matrix_b.inverse().unwrap());
     }
 
+    #[test]
+    fn multiplying_matrix_by_its_inverse_gives_identity() {
+        let matrices = [
+            Matrix4::new([
+                [-5.0, 2.0, 6.0, -8.0],
+                [1.0, -5.0, 1.0, 8.0],
+                [7.0, 7.0, -6.0, -7.0],
+                [1.0, -3.0, 7.0, 4.0],
+            ]),
+            Matrix4::new([
+                [8.0, -5.0, 9.0, 2.0],
+                [7.0, 5.0, 6.0, 1.0],
+                [-6.0, 0.0, 9.0, 6.0],
+                [-3.0, 0.0, -9.0, -4.0],
+            ]),
+            Matrix4::new([
+                [9.0, 3.0, 0.0, 9.0],
+                [-5.0, -2.0, -6.0, -3.0],
+                [-4.0, 9.0, 6.0, 4.0],
+                [-7.0, 6.0, 6.0, 2.0],
+            ]),
+        ];
+        for matrix in matrices {
+            assert_eq!(matrix * matrix.inverse().unwrap(), Matrix4::identity());
+            assert_eq!(matrix.inverse().unwrap() * matrix, Matrix4::identity());
+        }
+    }
+
     #[test]
     fn multiplying_by_translation_matrix() {
         let transform = Matrix4::translate(5.0, -3.0, 2.0);
@@ -942,4 +1152,51 @@ mod tests {
 
         assert_eq!(transformation * point, Point::new(15.0, 0.0, 7.0));
     }
+
+    #[test]
+    fn rotating_around_a_principal_axis_matches_the_axis_rotations() {
+        let r = PI / 3.0;
+        let x = Vector::new(2.0, 0.0, 0.0);
+        let y = Vector::new(0.0, 1.0, 0.0);
+        let z = Vector::new(0.0, 0.0, 1.0);
+        assert_eq!(Matrix4::rotate_axis(x, r), Matrix4::rotate_x(r));
+        assert_eq!(Matrix4::rotate_axis(y, r), Matrix4::rotate_y(r));
+        assert_eq!(Matrix4::rotate_axis(z, r), Matrix4::rotate_z(r));
+    }
+
+    #[test]
+    fn rotating_a_point_around_the_diagonal_axis() {
+        let diagonal = Vector::new(1.0, 1.0, 1.0);
+        let point = Point::new(1.0, 0.0, 0.0);
+        let quarter_turn = Matrix4::rotate_axis(diagonal, PI / 2.0);
+        assert_eq!(quarter_turn * point, Point::new(0.33333, 0.91068, -0.24402));
+        // A third of a turn around the diagonal swaps the axes
+        let third_turn = Matrix4::rotate_axis(diagonal, 2.0 * PI / 3.0);
+        assert_eq!(third_turn * point, Point::new(0.0, 1.0, 0.0));
+    }
+
+    #[test]
+    fn orientation_is_the_inverse_of_the_view_transform() {
+        let from = Point::new(1.0, 3.0, 2.0);
+        let to = Point::new(4.0, -2.0, 8.0);
+        let up = Vector::new(5.0, 3.0, 0.0).normalize();
+        let orientation = Matrix4::orientation(from, to, up);
+        assert_eq!(orientation, view_transform(from, to, up).inverse().unwrap());
+    }
+
+    #[test]
+    fn orientation_points_negative_z_at_the_target() {
+        let from = Point::new(0.0, 0.0, 0.0);
+        let to = Point::new(5.0, 0.0, 0.0);
+        let up = Vector::new(0.0, 1.0, 0.0);
+        let orientation = Matrix4::orientation(from, to, up);
+        assert_eq!(
+            orientation * Vector::new(0.0, 0.0, -1.0),
+            Vector::new(1.0, 0.0, 0.0)
+        );
+        assert_eq!(orientation * Vector::new(0.0, 1.0, 0.0), up);
+        // Tilted up vectors are squared up rather than skewing the object
+        let tilted = Matrix4::orientation(from, to, Vector::new(1.0, 1.0, 0.0));
+        assert_eq!(tilted * Vector::new(0.0, 1.0, 0.0), up);
+    }
 }
@@ -0,0 +1,411 @@
+use crate::materials::Material;
+use crate::objects::{Intersectable, Object};
+use crate::triangle::SmoothTriangle;
+use crate::tuples::{Point, Tuple, Vector};
+use std::collections::HashMap;
+use std::fmt;
+use std::fs;
+
+#[derive(Debug)]
+pub enum ObjError {
+    Io(std::io::Error),
+}
+
+impl fmt::Display for ObjError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ObjError::Io(e) => write!(f, "Could not read OBJ file: {}", e),
+        }
+    }
+}
+
+impl std::error::Error for ObjError {}
+
+impl From<std::io::Error> for ObjError {
+    fn from(e: std::io::Error) -> Self {
+        ObjError::Io(e)
+    }
+}
+
+// There's no Group shape in this tree, so instead of an Object::Group the loader hands back
+// plain lists of triangles: one for faces before any `g` statement and one per named group
+pub struct ObjParseResult {
+    pub vertices: Vec<Point>,
+    pub normals: Vec<Vector>,
+    pub ignored_lines: Vec<String>,
+    pub warnings: Vec<String>,
+    // Faces that appear before any `g` statement
+    pub default_group: Vec<Object>,
+    pub groups: HashMap<String, Vec<Object>>,
+}
+
+impl ObjParseResult {
+    pub fn group(&self, name: &str) -> Option<&Vec<Object>> {
+        self.groups.get(name)
+    }
+
+    // Gives every triangle in every group the same material, since OBJ files don't carry any
+    pub fn set_material(&mut self, material: Material) {
+        let groups = std::iter::once(&mut self.default_group).chain(self.groups.values_mut());
+        for object in groups.flatten() {
+            object.set_material(material);
+        }
+    }
+
+    // Replaces flat triangles with smooth ones, giving each corner the average normal of the
+    // faces around that vertex. Faces more than angle_threshold radians apart are left out of
+    // each other's averages, so sharp creases stay sharp. Each group is smoothed on its own.
+    pub fn smooth_normals(&mut self, angle_threshold: f64) {
+        let groups = std::iter::once(&mut self.default_group).chain(self.groups.values_mut());
+        for group in groups {
+            smooth_group(group, angle_threshold);
+        }
+    }
+
+    // Every triangle in the file, ready to be added to a World's objects
+    pub fn objects(&self) -> Vec<Object> {
+        let mut objects = self.default_group.clone();
+        for group in self.groups.values() {
+            objects.extend(group.iter().copied());
+        }
+        objects
+    }
+}
+
+pub fn parse_obj_file(path: &str) -> Result<ObjParseResult, ObjError> {
+    let contents = fs::read_to_string(path)?;
+    Ok(parse_obj_string(&contents))
+}
+
+pub fn parse_obj_string(contents: &str) -> ObjParseResult {
+    let mut result = ObjParseResult {
+        vertices: Vec::new(),
+        normals: Vec::new(),
+        ignored_lines: Vec::new(),
+        warnings: Vec::new(),
+        default_group: Vec::new(),
+        groups: HashMap::new(),
+    };
+    let mut current_group: Option<String> = None;
+
+    for (line_number, line) in contents.lines().enumerate() {
+        let mut words = line.split_whitespace();
+        let parsed = match words.next() {
+            None => Some(()),
+            Some("v") => parse_floats(words).map(|[x, y, z]| {
+                result.vertices.push(Point::new(x, y, z));
+            }),
+            Some("vn") => parse_floats(words).map(|[x, y, z]| {
+                result.normals.push(Vector::new(x, y, z));
+            }),
+            Some("f") => {
+                let references: Vec<&str> = words.collect();
+                match build_face(&result, &references) {
+                    Ok(triangles) => {
+                        match current_group {
+                            Some(ref name) => result
+                                .groups
+                                .entry(name.clone())
+                                .or_default()
+                                .extend(triangles),
+                            None => result.default_group.extend(triangles),
+                        }
+                        Some(())
+                    }
+                    Err(warning) => {
+                        result
+                            .warnings
+                            .push(format!("line {}: {}", line_number + 1, warning));
+                        Some(())
+                    }
+                }
+            }
+            Some("g") => words.next().map(|name| {
+                result.groups.entry(name.to_string()).or_default();
+                current_group = Some(name.to_string());
+            }),
+            _ => None,
+        };
+
+        if parsed.is_none() {
+            result.ignored_lines.push(line.to_string());
+        }
+    }
+
+    result
+}
+
+// Vertices are matched by their exact position, since faces sharing a vertex were all built
+// from the same `v` record
+fn vertex_key(point: Point) -> [u64; 3] {
+    [point.x.to_bits(), point.y.to_bits(), point.z.to_bits()]
+}
+
+fn smooth_group(group: &mut [Object], angle_threshold: f64) {
+    let min_cos = angle_threshold.cos();
+    let mut face_normals: HashMap<[u64; 3], Vec<Vector>> = HashMap::new();
+    for object in group.iter() {
+        if let Object::Triangle(ref t) = *object {
+            for point in [t.p1, t.p2, t.p3] {
+                face_normals
+                    .entry(vertex_key(point))
+                    .or_default()
+                    .push(t.normal);
+            }
+        }
+    }
+
+    let vertex_normal = |point: Point, normal: Vector| {
+        let sum = face_normals[&vertex_key(point)]
+            .iter()
+            .filter(|other| normal.dot(other) >= min_cos)
+            .fold(Vector::new(0.0, 0.0, 0.0), |sum, other| sum + *other);
+        sum.normalize()
+    };
+
+    for object in group.iter_mut() {
+        let Object::Triangle(t) = *object else {
+            continue;
+        };
+        // A degenerate triangle has no normal to share
+        if !t.normal.magnitude().is_finite() {
+            continue;
+        }
+        *object = Object::SmoothTriangle(SmoothTriangle {
+            transform: t.transform,
+            material: t.material,
+            ..SmoothTriangle::new(
+                t.p1,
+                t.p2,
+                t.p3,
+                vertex_normal(t.p1, t.normal),
+                vertex_normal(t.p2, t.normal),
+                vertex_normal(t.p3, t.normal),
+            )
+        });
+    }
+}
+
+fn parse_floats<'a>(mut words: impl Iterator<Item = &'a str>) -> Option<[f64; 3]> {
+    let x = words.next()?.parse().ok()?;
+    let y = words.next()?.parse().ok()?;
+    let z = words.next()?.parse().ok()?;
+    Some([x, y, z])
+}
+
+// OBJ indices are 1-based, and negative indices count back from the most recent entry
+fn resolve_index(index: &str, len: usize) -> Result<usize, String> {
+    let index: i64 = index
+        .parse()
+        .map_err(|_| format!("invalid index '{}'", index))?;
+    let resolved = if index > 0 {
+        index - 1
+    } else {
+        len as i64 + index
+    };
+    if index == 0 || resolved < 0 || resolved >= len as i64 {
+        return Err(format!("index {} is out of range", index));
+    }
+    Ok(resolved as usize)
+}
+
+// Fan-triangulates a polygon face, producing smooth triangles when every vertex has a normal
+fn build_face(result: &ObjParseResult, references: &[&str]) -> Result<Vec<Object>, String> {
+    if references.len() < 3 {
+        return Err(format!("face needs 3 vertices, got {}", references.len()));
+    }
+
+    let mut vertices = Vec::new();
+    let mut normals = Vec::new();
+    for reference in references {
+        // Each reference is one of v, v/vt, v//vn or v/vt/vn
+        let parts: Vec<&str> = reference.split('/').collect();
+        vertices.push(result.vertices[resolve_index(parts[0], result.vertices.len())?]);
+        if let Some(normal) = parts.get(2).filter(|n| !n.is_empty()) {
+            normals.push(result.normals[resolve_index(normal, result.normals.len())?]);
+        }
+    }
+
+    let smooth = normals.len() == vertices.len();
+    let triangles = (1..vertices.len() - 1)
+        .map(|i| {
+            if smooth {
+                Object::smooth_triangle(
+                    vertices[0],
+                    vertices[i],
+                    vertices[i + 1],
+                    normals[0],
+                    normals[i],
+                    normals[i + 1],
+                )
+            } else {
+                Object::triangle(vertices[0], vertices[i], vertices[i + 1])
+            }
+        })
+        .collect();
+    Ok(triangles)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::color::Color;
+    use crate::materials::Material;
+    use crate::obj_loader::{parse_obj_file, parse_obj_string, ObjError};
+    use crate::objects::{Intersectable, Object};
+    use crate::tuples::{Point, Tuple, Vector};
+    use std::f64::consts::PI;
+
+    #[test]
+    fn ignoring_unrecognized_lines() {
+        let gibberish = "There was a young lady named Bright\n\
+                         who traveled much faster than light.\n\
+                         She set out one day\n\
+                         in a relative way,\n\
+                         and came back the previous night.";
+        let result = parse_obj_string(gibberish);
+        assert_eq!(result.ignored_lines.len(), 5);
+        assert!(result.default_group.is_empty());
+    }
+
+    #[test]
+    fn vertex_records() {
+        let file = "v -1 1 0\nv -1.0000 0.5000 0.0000\nv 1 0 0\nv 1 1 0";
+        let result = parse_obj_string(file);
+        assert_eq!(result.vertices[0], Point::new(-1.0, 1.0, 0.0));
+        assert_eq!(result.vertices[1], Point::new(-1.0, 0.5, 0.0));
+        assert_eq!(result.vertices[2], Point::new(1.0, 0.0, 0.0));
+        assert_eq!(result.vertices[3], Point::new(1.0, 1.0, 0.0));
+    }
+
+    #[test]
+    fn parsing_triangle_faces() {
+        let file = "v -1 1 0\nv -1 0 0\nv 1 0 0\nv 1 1 0\n\nf 1 2 3\nf 1 3 4";
+        let result = parse_obj_string(file);
+        let v = &result.vertices;
+        assert_eq!(result.default_group.len(), 2);
+        assert_eq!(result.default_group[0], Object::triangle(v[0], v[1], v[2]));
+        assert_eq!(result.default_group[1], Object::triangle(v[0], v[2], v[3]));
+    }
+
+    #[test]
+    fn triangulating_polygons() {
+        let file = "v -1 1 0\nv -1 0 0\nv 1 0 0\nv 1 1 0\nv 0 2 0\n\nf 1 2 3 4 5";
+        let result = parse_obj_string(file);
+        let v = &result.vertices;
+        assert_eq!(result.default_group.len(), 3);
+        assert_eq!(result.default_group[0], Object::triangle(v[0], v[1], v[2]));
+        assert_eq!(result.default_group[1], Object::triangle(v[0], v[2], v[3]));
+        assert_eq!(result.default_group[2], Object::triangle(v[0], v[3], v[4]));
+    }
+
+    #[test]
+    fn triangles_in_groups() {
+        let file = "v -1 1 0\nv -1 0 0\nv 1 0 0\nv 1 1 0\n\
+                    g FirstGroup\nf 1 2 3\ng SecondGroup\nf 1 3 4";
+        let result = parse_obj_string(file);
+        let v = &result.vertices;
+        let first = result.group("FirstGroup").unwrap();
+        let second = result.group("SecondGroup").unwrap();
+        assert_eq!(first[0], Object::triangle(v[0], v[1], v[2]));
+        assert_eq!(second[0], Object::triangle(v[0], v[2], v[3]));
+        assert_eq!(result.objects().len(), 2);
+    }
+
+    #[test]
+    fn setting_the_material_of_every_triangle() {
+        let file = "v -1 1 0\nv -1 0 0\nv 1 0 0\nv 1 1 0\n\
+                    f 1 2 3\ng FirstGroup\nf 1 2 3\ng SecondGroup\nf 1 3 4";
+        let mut result = parse_obj_string(file);
+        let material = Material {
+            color: Color::green(),
+            ..Default::default()
+        };
+        result.set_material(material);
+        assert_eq!(result.objects().len(), 3);
+        assert!(result
+            .objects()
+            .iter()
+            .all(|object| object.material() == material));
+    }
+
+    #[test]
+    fn vertex_normal_records() {
+        let file = "vn 0 0 1\nvn 0.707 0 -0.707\nvn 1 2 3";
+        let result = parse_obj_string(file);
+        assert_eq!(result.normals[0], Vector::new(0.0, 0.0, 1.0));
+        assert_eq!(result.normals[1], Vector::new(0.707, 0.0, -0.707));
+        assert_eq!(result.normals[2], Vector::new(1.0, 2.0, 3.0));
+    }
+
+    #[test]
+    fn faces_with_normals() {
+        let file = "v 0 1 0\nv -1 0 0\nv 1 0 0\n\
+                    vn -1 0 0\nvn 1 0 0\nvn 0 1 0\n\
+                    f 1//3 2//1 3//2\nf 1/0/3 2/102/1 3/14/2";
+        let result = parse_obj_string(file);
+        let v = &result.vertices;
+        let n = &result.normals;
+        let expected = Object::smooth_triangle(v[0], v[1], v[2], n[2], n[0], n[1]);
+        assert_eq!(result.default_group[0], expected);
+        assert_eq!(result.default_group[1], expected);
+    }
+
+    #[test]
+    fn negative_indices_are_relative_to_the_end() {
+        let file = "v -1 1 0\nv -1 0 0\nv 1 0 0\nf -3 -2 -1";
+        let result = parse_obj_string(file);
+        let v = &result.vertices;
+        assert_eq!(result.default_group[0], Object::triangle(v[0], v[1], v[2]));
+    }
+
+    #[test]
+    fn out_of_range_indices_are_skipped_with_a_warning() {
+        let file = "v -1 1 0\nv -1 0 0\nv 1 0 0\nf 1 2 4\nf 0 1 2\nf 1 2 3";
+        let result = parse_obj_string(file);
+        assert_eq!(result.default_group.len(), 1);
+        assert_eq!(result.warnings.len(), 2);
+        assert!(result.warnings[0].starts_with("line 4"));
+    }
+
+    #[test]
+    fn missing_file_returns_an_error() {
+        let result = parse_obj_file("does/not/exist.obj");
+        assert!(matches!(result, Err(ObjError::Io(_))));
+    }
+
+    #[test]
+    fn smoothing_normals_averages_faces_that_share_a_vertex() {
+        let file = "v 0 1 0\nv -1 0 0\nv 1 0 0\nv 0 -1 -1\n\
+                    f 1 2 3\nf 4 3 2";
+        let mut result = parse_obj_string(file);
+        let v = result.vertices.clone();
+        let upper = Vector::new(0.0, 0.0, -1.0);
+        let lower = Vector::new(0.0, 1.0, -1.0).normalize();
+        let shared = (upper + lower).normalize();
+
+        result.smooth_normals(PI / 3.0);
+        assert_eq!(
+            result.default_group[0],
+            Object::smooth_triangle(v[0], v[1], v[2], upper, shared, shared)
+        );
+        assert_eq!(
+            result.default_group[1],
+            Object::smooth_triangle(v[3], v[2], v[1], lower, shared, shared)
+        );
+    }
+
+    #[test]
+    fn smoothing_normals_keeps_creases_sharper_than_the_threshold() {
+        let file = "v 0 1 0\nv -1 0 0\nv 1 0 0\nv 0 -1 -1\n\
+                    f 1 2 3\nf 4 3 2";
+        let mut result = parse_obj_string(file);
+        let v = result.vertices.clone();
+        let upper = Vector::new(0.0, 0.0, -1.0);
+
+        result.smooth_normals(PI / 6.0);
+        assert_eq!(
+            result.default_group[0],
+            Object::smooth_triangle(v[0], v[1], v[2], upper, upper, upper)
+        );
+    }
+}
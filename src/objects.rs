@@ -1,14 +1,21 @@
+use crate::bounds::Bounds;
+use crate::disk::Disk;
 use crate::intersections::Intersection;
 use crate::materials::Material;
-use crate::matrices::Matrix4;
+use crate::matrices::{Matrix4, MatrixError};
+use crate::quad::Quad;
 use crate::rays::Ray;
 use crate::sphere::Sphere;
+use crate::torus::Torus;
+use crate::transformations::Transform;
+use crate::triangle::{SmoothTriangle, Triangle};
 use crate::tuples::{Point, Vector};
 
 pub trait Intersectable {
     fn intersect(&self, r: Ray) -> Vec<f64>;
-    fn intersect_with_object(&self, r: Ray) -> Vec<Intersection>;
+    fn intersect_with_object(&self, r: Ray) -> Vec<Intersection<'_>>;
     fn normal_at(&self, p: Point) -> Vector;
+    fn normal_at_hit(&self, p: Point, hit: &Intersection) -> Vector;
     fn material(&self) -> Material;
     fn transformation(&self) -> Matrix4;
     fn set_transform(&mut self, transformation: Matrix4);
@@ -18,52 +25,460 @@ pub trait Intersectable {
 #[derive(Debug, Copy, Clone, PartialEq)]
 pub enum Object {
     Sphere(Sphere),
+    Triangle(Triangle),
+    SmoothTriangle(SmoothTriangle),
+    Disk(Disk),
+    Torus(Torus),
+    Quad(Quad),
+}
+
+impl Object {
+    pub fn sphere_builder() -> ObjectBuilder {
+        ObjectBuilder::new(Object::Sphere(Sphere::new()))
+    }
+
+    pub fn triangle_builder(p1: Point, p2: Point, p3: Point) -> ObjectBuilder {
+        ObjectBuilder::new(Object::triangle(p1, p2, p3))
+    }
+
+    pub fn disk_builder(inner_radius: f64, outer_radius: f64) -> ObjectBuilder {
+        ObjectBuilder::new(Object::disk(inner_radius, outer_radius))
+    }
+
+    pub fn quad(u_min: f64, u_max: f64, v_min: f64, v_max: f64) -> Object {
+        Object::Quad(Quad::new(u_min, u_max, v_min, v_max))
+    }
+
+    pub fn torus_builder(major_radius: f64, minor_radius: f64) -> ObjectBuilder {
+        ObjectBuilder::new(Object::torus(major_radius, minor_radius))
+    }
+
+    pub fn quad_builder(u_min: f64, u_max: f64, v_min: f64, v_max: f64) -> ObjectBuilder {
+        ObjectBuilder::new(Object::quad(u_min, u_max, v_min, v_max))
+    }
+
+    // For shapes without a dedicated builder, like smooth triangles
+    pub fn builder(self) -> ObjectBuilder {
+        ObjectBuilder::new(self)
+    }
+
+    pub fn triangle(p1: Point, p2: Point, p3: Point) -> Object {
+        Object::Triangle(Triangle::new(p1, p2, p3))
+    }
+
+    pub fn smooth_triangle(
+        p1: Point,
+        p2: Point,
+        p3: Point,
+        n1: Vector,
+        n2: Vector,
+        n3: Vector,
+    ) -> Object {
+        Object::SmoothTriangle(SmoothTriangle::new(p1, p2, p3, n1, n2, n3))
+    }
+
+    pub fn disk(inner_radius: f64, outer_radius: f64) -> Object {
+        Object::Disk(Disk::new(inner_radius, outer_radius))
+    }
+
+    pub fn torus(major_radius: f64, minor_radius: f64) -> Object {
+        Object::Torus(Torus::new(major_radius, minor_radius))
+    }
+
+    // Like set_transform, but reports a transform that can't be inverted. The transform is
+    // applied either way, leaving the object degenerate
+    pub fn try_set_transform(&mut self, transformation: Matrix4) -> Result<(), MatrixError> {
+        self.set_transform(transformation);
+        self.transform().try_inverse().map(|_| ())
+    }
+
+    // An object whose transform can't be inverted, e.g. a scale of zero on one axis, has been
+    // squashed flat. Rays skip it rather than panicking mid-render
+    pub fn is_degenerate(&self) -> bool {
+        self.transform().is_degenerate()
+    }
+
+    pub fn transform(&self) -> &Transform {
+        match *self {
+            Object::Sphere(ref s) => &s.transform,
+            Object::Triangle(ref t) => &t.transform,
+            Object::SmoothTriangle(ref t) => &t.transform,
+            Object::Disk(ref d) => &d.transform,
+            Object::Quad(ref q) => &q.transform,
+            Object::Torus(ref t) => &t.transform,
+        }
+    }
+
+    fn transform_mut(&mut self) -> &mut Transform {
+        match *self {
+            Object::Sphere(ref mut s) => &mut s.transform,
+            Object::Triangle(ref mut t) => &mut t.transform,
+            Object::SmoothTriangle(ref mut t) => &mut t.transform,
+            Object::Disk(ref mut d) => &mut d.transform,
+            Object::Quad(ref mut q) => &mut q.transform,
+            Object::Torus(ref mut t) => &mut t.transform,
+        }
+    }
+
+    // A short label for the kind of shape, for logging and scene summaries
+    pub fn kind_name(&self) -> &'static str {
+        match *self {
+            Object::Sphere(_) => "sphere",
+            Object::Triangle(_) => "triangle",
+            Object::SmoothTriangle(_) => "smooth_triangle",
+            Object::Disk(_) => "disk",
+            Object::Quad(_) => "quad",
+            Object::Torus(_) => "torus",
+        }
+    }
+
+    pub fn motion(&self) -> Option<Matrix4> {
+        self.transform().motion()
+    }
+
+    // Makes the object move from its transformation at time 0.0 to this one at time 1.0
+    pub fn set_motion(&mut self, motion: Matrix4) {
+        self.transform_mut().set_motion(motion);
+        self.save_world_bounds();
+    }
+
+    // Maps world space at the given time back to where the object sits at time 0.0. Moving
+    // rays and points through this lets shapes keep using their cached inverse, rather than
+    // being rebuilt for every ray. None for objects that don't move.
+    pub fn motion_transform(&self, time: f64) -> Option<Matrix4> {
+        self.transform().motion_at(time)
+    }
+
+    fn undo_motion(&self, r: Ray) -> Ray {
+        match self.motion_transform(r.time) {
+            Some(transform) => r.transform(transform),
+            None => r,
+        }
+    }
+
+    // Bounds in the shape's own object space
+    pub fn bounds(&self) -> Bounds {
+        match *self {
+            Object::Sphere(ref s) => s.bounds(),
+            Object::Triangle(ref t) => t.bounds(),
+            Object::SmoothTriangle(ref t) => t.bounds(),
+            Object::Disk(ref d) => d.bounds(),
+            Object::Quad(ref q) => q.bounds(),
+            Object::Torus(ref t) => t.bounds(),
+        }
+    }
+
+    pub fn parent_space_bounds(&self) -> Bounds {
+        self.bounds().transform(self.transformation())
+    }
+
+    // Objects are never nested, so their parent space is world space. Moving objects are
+    // bounded over their whole path.
+    pub fn world_bounds(&self) -> Bounds {
+        self.transform().bounds_in_world(self.bounds())
+    }
+
+    // Keeps world_bounds with the transform, for World to cull with. Changing the transform
+    // through the object saves them again. Changing a shape's own fields, like a torus's
+    // radii, needs this to be called again.
+    pub fn save_world_bounds(&mut self) {
+        let local = self.bounds();
+        self.transform_mut().save_world_bounds(local);
+    }
 }
 
 impl Intersectable for Object {
     fn intersect(&self, r: Ray) -> Vec<f64> {
+        if self.is_degenerate() {
+            return vec![];
+        }
+        let r = self.undo_motion(r);
         match *self {
             Object::Sphere(ref s) => s.intersect(r),
+            Object::Triangle(ref t) => t.intersect(r),
+            Object::SmoothTriangle(ref t) => t.intersect(r),
+            Object::Disk(ref d) => d.intersect(r),
+            Object::Quad(ref q) => q.intersect(r),
+            Object::Torus(ref t) => t.intersect(r),
         }
     }
 
-    fn intersect_with_object(&self, r: Ray) -> Vec<Intersection> {
+    fn intersect_with_object(&self, r: Ray) -> Vec<Intersection<'_>> {
+        if self.is_degenerate() {
+            return vec![];
+        }
+        let r = self.undo_motion(r);
         match *self {
             Object::Sphere(ref s) => s
                 .intersect(r)
                 .iter()
                 .map(|t| Intersection::new(*t, self))
                 .collect(),
+            Object::Triangle(ref tri) => tri
+                .intersect_with_uv(r)
+                .iter()
+                .map(|(t, u, v)| Intersection::with_uv(*t, self, *u, *v))
+                .collect(),
+            Object::SmoothTriangle(ref tri) => tri
+                .intersect_with_uv(r)
+                .iter()
+                .map(|(t, u, v)| Intersection::with_uv(*t, self, *u, *v))
+                .collect(),
+            Object::Disk(ref d) => d
+                .intersect(r)
+                .iter()
+                .map(|t| Intersection::new(*t, self))
+                .collect(),
+            Object::Torus(ref torus) => torus
+                .intersect(r)
+                .iter()
+                .map(|t| Intersection::new(*t, self))
+                .collect(),
+            Object::Quad(ref q) => q
+                .intersect(r)
+                .iter()
+                .map(|t| Intersection::new(*t, self))
+                .collect(),
         }
     }
 
     fn normal_at(&self, p: Point) -> Vector {
         match *self {
             Object::Sphere(ref s) => s.normal_at(p),
+            Object::Triangle(ref t) => t.normal_at(p),
+            Object::SmoothTriangle(ref t) => t.normal_at(p),
+            Object::Disk(ref d) => d.normal_at(p),
+            Object::Quad(ref q) => q.normal_at(p),
+            Object::Torus(ref t) => t.normal_at(p),
+        }
+    }
+
+    // Smooth triangles need the hit's u/v to interpolate their normal
+    fn normal_at_hit(&self, p: Point, hit: &Intersection) -> Vector {
+        match (*self, hit.uv) {
+            (Object::SmoothTriangle(ref t), Some((u, v))) => t.normal_at_uv(p, u, v),
+            _ => self.normal_at(p),
         }
     }
 
     fn material(&self) -> Material {
         match *self {
             Object::Sphere(ref s) => s.material,
+            Object::Triangle(ref t) => t.material,
+            Object::SmoothTriangle(ref t) => t.material,
+            Object::Disk(ref d) => d.material,
+            Object::Quad(ref q) => q.material,
+            Object::Torus(ref t) => t.material,
         }
     }
 
     fn transformation(&self) -> Matrix4 {
-        match *self {
-            Object::Sphere(ref s) => s.transformation,
-        }
+        self.transform().matrix()
     }
 
     fn set_transform(&mut self, transformation: Matrix4) {
-        match *self {
-            Object::Sphere(ref mut s) => s.set_transform(transformation),
-        }
+        self.transform_mut().set_matrix(transformation);
+        self.save_world_bounds();
     }
 
     fn set_material(&mut self, material: Material) {
         match *self {
             Object::Sphere(ref mut s) => s.set_material(material),
+            Object::Triangle(ref mut t) => t.set_material(material),
+            Object::SmoothTriangle(ref mut t) => t.set_material(material),
+            Object::Disk(ref mut d) => d.set_material(material),
+            Object::Quad(ref mut q) => q.set_material(material),
+            Object::Torus(ref mut t) => t.set_material(material),
+        }
+    }
+}
+
+// Sets an object's transform and material inline, instead of mutating it after construction
+#[derive(Debug, Clone, Copy)]
+pub struct ObjectBuilder {
+    object: Object,
+}
+
+impl ObjectBuilder {
+    pub fn new(object: Object) -> ObjectBuilder {
+        ObjectBuilder { object }
+    }
+
+    pub fn transform(mut self, transformation: Matrix4) -> ObjectBuilder {
+        self.object.set_transform(transformation);
+        self
+    }
+
+    pub fn material(mut self, material: Material) -> ObjectBuilder {
+        self.object.set_material(material);
+        self
+    }
+
+    pub fn build(self) -> Object {
+        self.object
+    }
+
+    // Like build, but fails if the transform can't be inverted, rather than giving back an
+    // object that will never be seen
+    pub fn try_build(self) -> Result<Object, MatrixError> {
+        self.object.transform().try_inverse().map(|_| self.object)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::color::Color;
+    use crate::materials::Material;
+    use crate::matrices::{Matrix4, MatrixError};
+    use crate::objects::{Intersectable, Object};
+    use crate::rays::Ray;
+    use crate::sphere::Sphere;
+    use crate::transformations::TransformBuilder;
+    use crate::tuples::{Point, Tuple, Vector};
+
+    #[test]
+    fn building_a_sphere_matches_setting_fields_afterwards() {
+        let transformation = TransformBuilder::new()
+            .scale(2.0, 2.0, 2.0)
+            .translate(0.0, 1.0, 0.0)
+            .build();
+        let material = Material {
+            color: Color::red(),
+            ..Default::default()
+        };
+
+        let mut expected = Object::Sphere(Sphere::new());
+        expected.set_transform(transformation);
+        expected.set_material(material);
+
+        let built = Object::sphere_builder()
+            .transform(transformation)
+            .material(material)
+            .build();
+        assert_eq!(built, expected);
+    }
+
+    #[test]
+    fn builder_defaults_match_the_plain_constructor() {
+        assert_eq!(
+            Object::sphere_builder().build(),
+            Object::Sphere(Sphere::new())
+        );
+        assert_eq!(
+            Object::disk_builder(0.5, 1.0).build(),
+            Object::disk(0.5, 1.0)
+        );
+        assert_eq!(
+            Object::torus_builder(1.0, 0.25).build(),
+            Object::torus(1.0, 0.25)
+        );
+    }
+
+    #[test]
+    fn any_object_can_be_turned_into_a_builder() {
+        let p1 = Point::new(0.0, 1.0, 0.0);
+        let p2 = Point::new(-1.0, 0.0, 0.0);
+        let p3 = Point::new(1.0, 0.0, 0.0);
+        let triangle = Object::triangle(p1, p2, p3)
+            .builder()
+            .transform(Matrix4::translate(0.0, 0.0, 5.0))
+            .build();
+        assert_eq!(triangle.transformation(), Matrix4::translate(0.0, 0.0, 5.0));
+        assert_eq!(
+            Object::triangle_builder(p1, p2, p3).build(),
+            Object::triangle(p1, p2, p3)
+        );
+    }
+
+    #[test]
+    fn objects_with_a_non_invertible_transform_are_missed() {
+        let mut sphere = Object::sphere_builder().build();
+        let result = sphere.try_set_transform(Matrix4::scale(1.0, 0.0, 1.0));
+        assert_eq!(result, Err(MatrixError::NotInvertible));
+        assert!(sphere.is_degenerate());
+
+        let ray = Ray::new(Point::new(0.0, 0.0, -5.0), Vector::new(0.0, 0.0, 1.0));
+        assert!(sphere.intersect(ray).is_empty());
+        assert!(sphere.intersect_with_object(ray).is_empty());
+    }
+
+    #[test]
+    fn setting_an_invertible_transform_succeeds() {
+        let mut sphere = Object::sphere_builder().build();
+        assert_eq!(
+            sphere.try_set_transform(Matrix4::scale(2.0, 2.0, 2.0)),
+            Ok(())
+        );
+        assert!(!sphere.is_degenerate());
+    }
+
+    #[test]
+    fn small_objects_are_not_degenerate() {
+        let sphere = Object::sphere_builder()
+            .transform(Matrix4::scale(0.02, 0.02, 0.02))
+            .build();
+        assert!(!sphere.is_degenerate());
+        let ray = Ray::new(Point::new(0.0, 0.0, -5.0), Vector::new(0.0, 0.0, 1.0));
+        assert_eq!(sphere.intersect(ray).len(), 2);
+    }
+
+    #[test]
+    fn building_with_a_non_invertible_transform_reports_it() {
+        let flat = Object::sphere_builder().transform(Matrix4::scale(1.0, 0.0, 1.0));
+        assert_eq!(flat.try_build(), Err(MatrixError::NotInvertible));
+        assert!(flat.build().is_degenerate());
+
+        let sphere = Object::sphere_builder().transform(Matrix4::scale(2.0, 2.0, 2.0));
+        assert_eq!(sphere.try_build(), Ok(sphere.build()));
+    }
+
+    #[test]
+    fn moving_objects_are_hit_where_they_are_at_the_ray_time() {
+        let mut sphere = Object::sphere_builder().build();
+        sphere.set_motion(Matrix4::translate(4.0, 0.0, 0.0));
+
+        let origin = Point::new(2.0, 0.0, -5.0);
+        let direction = Vector::new(0.0, 0.0, 1.0);
+        assert!(sphere
+            .intersect(Ray::at_time(origin, direction, 0.0))
+            .is_empty());
+        assert_eq!(
+            sphere.intersect(Ray::at_time(origin, direction, 0.5)),
+            vec![4.0, 6.0]
+        );
+        assert!(sphere
+            .intersect(Ray::at_time(origin, direction, 1.0))
+            .is_empty());
+    }
+
+    #[test]
+    fn moving_objects_are_bounded_over_their_whole_path() {
+        let mut sphere = Object::sphere_builder().build();
+        sphere.set_motion(Matrix4::translate(0.0, 3.0, 0.0));
+        let bounds = sphere.world_bounds();
+        assert_eq!(bounds.min, Point::new(-1.0, -1.0, -1.0));
+        assert_eq!(bounds.max, Point::new(1.0, 4.0, 1.0));
+    }
+
+    #[test]
+    fn objects_report_their_kind() {
+        let p1 = Point::new(0.0, 1.0, 0.0);
+        let p2 = Point::new(-1.0, 0.0, 0.0);
+        let p3 = Point::new(1.0, 0.0, 0.0);
+        let n = Vector::new(0.0, 0.0, -1.0);
+        let cases = [
+            (Object::Sphere(Sphere::new()), "sphere"),
+            (Object::triangle(p1, p2, p3), "triangle"),
+            (
+                Object::smooth_triangle(p1, p2, p3, n, n, n),
+                "smooth_triangle",
+            ),
+            (Object::disk(0.0, 1.0), "disk"),
+            (Object::quad(-1.0, 1.0, -1.0, 1.0), "quad"),
+            (Object::torus(1.0, 0.25), "torus"),
+        ];
+        for (object, expected) in cases {
+            assert_eq!(object.kind_name(), expected);
         }
     }
 }
@@ -0,0 +1,13 @@
+// The types most scene programs need, so `use raytracer::prelude::*;` is enough to get started
+pub use crate::camera::{Camera, CameraBuilder};
+pub use crate::canvas::Canvas;
+pub use crate::color::Color;
+pub use crate::lights::{Light, PointLight};
+pub use crate::materials::Material;
+pub use crate::matrices::Matrix4;
+pub use crate::objects::{Intersectable, Object};
+pub use crate::rays::Ray;
+pub use crate::sphere::Sphere;
+pub use crate::transformations::{view_transform, TransformBuilder};
+pub use crate::tuples::{Point, Tuple, Vector};
+pub use crate::world::World;
@@ -0,0 +1,125 @@
+use crate::bounds::Bounds;
+use crate::floats::EPSILON;
+use crate::materials::Material;
+use crate::matrices::Matrix4;
+use crate::rays::Ray;
+use crate::transformations::Transform;
+use crate::tuples::{Point, Tuple, Vector};
+
+// A rectangle in the xz plane, covering u_min..u_max along x and v_min..v_max along z
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct Quad {
+    pub u_min: f64,
+    pub u_max: f64,
+    pub v_min: f64,
+    pub v_max: f64,
+    pub transform: Transform,
+    pub material: Material,
+}
+
+impl Quad {
+    pub fn new(u_min: f64, u_max: f64, v_min: f64, v_max: f64) -> Quad {
+        Quad {
+            u_min,
+            u_max,
+            v_min,
+            v_max,
+            transform: Transform::default(),
+            material: Material::default(),
+        }
+    }
+
+    // Returns list of time values where the ray intersects the quad
+    pub fn intersect(&self, ray: Ray) -> Vec<f64> {
+        let ray = ray.transform(self.transform.inverse());
+
+        // Ray is parallel to the quad's plane
+        if ray.direction.y.abs() < EPSILON {
+            return vec![];
+        }
+
+        let t = -ray.origin.y / ray.direction.y;
+        let point = ray.position(t);
+        if !(self.u_min..=self.u_max).contains(&point.x)
+            || !(self.v_min..=self.v_max).contains(&point.z)
+        {
+            return vec![];
+        }
+        vec![t]
+    }
+
+    // The normal is the same everywhere on a flat quad
+    pub fn normal_at(&self, _point: Point) -> Vector {
+        let world_normal = self.transform.inverse().transpose() * Vector::new(0.0, 1.0, 0.0);
+        world_normal.normalize()
+    }
+
+    pub fn bounds(&self) -> Bounds {
+        Bounds::new(
+            Point::new(self.u_min, 0.0, self.v_min),
+            Point::new(self.u_max, 0.0, self.v_max),
+        )
+    }
+
+    pub fn set_transform(&mut self, transformation: Matrix4) {
+        self.transform.set_matrix(transformation);
+    }
+
+    pub fn set_material(&mut self, material: Material) {
+        self.material = material;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::matrices::Matrix4;
+    use crate::objects::{Intersectable, Object};
+    use crate::quad::Quad;
+    use crate::rays::Ray;
+    use crate::tuples::{Point, Tuple, Vector};
+    use std::f64::consts::PI;
+
+    #[test]
+    fn ray_hits_a_quad() {
+        let quad = Quad::new(-1.0, 1.0, -1.5, 1.5);
+        let ray = Ray::new(Point::new(0.5, 2.0, 1.25), Vector::new(0.0, -1.0, 0.0));
+        assert_eq!(quad.intersect(ray), vec![2.0]);
+    }
+
+    #[test]
+    fn ray_misses_outside_the_quad() {
+        let quad = Quad::new(-1.0, 1.0, -1.5, 1.5);
+        let cases = [
+            Point::new(1.5, 1.0, 0.0),
+            Point::new(-1.5, 1.0, 0.0),
+            Point::new(0.0, 1.0, 2.0),
+            Point::new(0.0, 1.0, -2.0),
+        ];
+        for origin in cases {
+            let ray = Ray::new(origin, Vector::new(0.0, -1.0, 0.0));
+            assert!(quad.intersect(ray).is_empty());
+        }
+    }
+
+    #[test]
+    fn ray_parallel_to_a_quad_misses() {
+        let quad = Quad::new(-1.0, 1.0, -1.0, 1.0);
+        let ray = Ray::new(Point::new(0.0, 0.0, -2.0), Vector::new(0.0, 0.0, 1.0));
+        assert!(quad.intersect(ray).is_empty());
+    }
+
+    #[test]
+    fn normal_of_a_quad_standing_up_as_a_wall() {
+        let mut quad = Object::quad(-1.0, 1.0, -1.5, 1.5);
+        quad.set_transform(Matrix4::rotate_x(-PI / 2.0));
+        let normal = quad.normal_at(Point::new(0.0, 0.0, 0.0));
+        assert_eq!(normal, Vector::new(0.0, 0.0, -1.0));
+    }
+
+    #[test]
+    fn quad_bounds_are_finite() {
+        let bounds = Quad::new(-1.0, 1.0, -1.5, 1.5).bounds();
+        assert_eq!(bounds.min, Point::new(-1.0, 0.0, -1.5));
+        assert_eq!(bounds.max, Point::new(1.0, 0.0, 1.5));
+    }
+}
@@ -5,11 +5,25 @@ use crate::tuples::{Point, Vector};
 pub struct Ray {
     pub origin: Point,
     pub direction: Vector,
+    // When during the frame the ray was cast, from 0.0 to 1.0. Only moving objects care.
+    pub time: f64,
 }
 
 impl Ray {
     pub fn new(origin: Point, direction: Vector) -> Ray {
-        Ray { origin, direction }
+        Ray {
+            origin,
+            direction,
+            time: 0.0,
+        }
+    }
+
+    pub fn at_time(origin: Point, direction: Vector, time: f64) -> Ray {
+        Ray {
+            origin,
+            direction,
+            time,
+        }
     }
 
     pub fn position(self, t: f64) -> Point {
@@ -17,7 +31,11 @@ impl Ray {
     }
 
     pub fn transform(self, matrix: Matrix4) -> Ray {
-        Ray::new(matrix * self.origin, matrix * self.direction)
+        Ray {
+            origin: matrix * self.origin,
+            direction: matrix * self.direction,
+            time: self.time,
+        }
     }
 }
 
@@ -34,6 +52,15 @@ mod tests {
         let ray = Ray::new(origin, direction);
         assert_eq!(ray.origin, origin);
         assert_eq!(ray.direction, direction);
+        assert_eq!(ray.time, 0.0);
+    }
+
+    #[test]
+    fn transforming_a_ray_keeps_its_time() {
+        let ray = Ray::at_time(Point::new(1.0, 2.0, 3.0), Vector::new(0.0, 1.0, 0.0), 0.5);
+        let transformed = ray.transform(Matrix4::translate(3.0, 4.0, 5.0));
+        assert_eq!(transformed.origin, Point::new(4.0, 6.0, 8.0));
+        assert_eq!(transformed.time, 0.5);
     }
 
     #[test]
@@ -0,0 +1,116 @@
+use crate::tuples::{Point, Vector};
+use std::f64::consts::PI;
+
+// A small xorshift random number generator. Not suitable for anything but sampling, but it
+// is fast and gives the same sequence for the same seed, so renders are reproducible.
+#[derive(Debug, Clone, Copy)]
+pub struct Rng {
+    state: u64,
+}
+
+impl Rng {
+    pub fn new(seed: u64) -> Rng {
+        // xorshift gets stuck on a state of zero, and similar seeds give similar early
+        // output, so scramble the seed first (this is the splitmix64 finalizer)
+        let mut z = seed.wrapping_add(0x9E37_79B9_7F4A_7C15);
+        z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+        z ^= z >> 31;
+        Rng {
+            state: if z == 0 { 1 } else { z },
+        }
+    }
+
+    // Each pixel gets its own sequence, so the result doesn't depend on the order, or the
+    // thread, pixels are rendered in
+    pub fn for_pixel(x: u32, y: u32, seed: u64) -> Rng {
+        Rng::new(seed ^ ((x as u64) << 32 | y as u64).wrapping_mul(0x2545_F491_4F6C_DD1D))
+    }
+
+    // For sampling that should differ from one shaded point to the next, but come out the
+    // same every time the same point is shaded
+    pub fn for_point(point: Point) -> Rng {
+        Rng::new(
+            point.x.to_bits()
+                ^ point.y.to_bits().rotate_left(21)
+                ^ point.z.to_bits().rotate_left(42),
+        )
+    }
+
+    pub fn next_u64(&mut self) -> u64 {
+        let mut x = self.state;
+        x ^= x << 13;
+        x ^= x >> 7;
+        x ^= x << 17;
+        self.state = x;
+        x
+    }
+
+    // Uniformly distributed in [0.0, 1.0)
+    pub fn next_f64(&mut self) -> f64 {
+        (self.next_u64() >> 11) as f64 / (1u64 << 53) as f64
+    }
+}
+
+// A random direction on the hemisphere around normal, more likely near the normal than
+// the horizon (cosine-weighted), which is how diffuse surfaces gather light
+pub fn sample_hemisphere(normal: Vector, rng: &mut Rng) -> Vector {
+    let (tangent, bitangent, normal) = normal.orthonormal_basis();
+    let radius = rng.next_f64().sqrt();
+    let theta = 2.0 * PI * rng.next_f64();
+    tangent * (radius * theta.cos())
+        + bitangent * (radius * theta.sin())
+        + normal * (1.0 - radius * radius).sqrt()
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::floats::float_equal;
+    use crate::rng::{sample_hemisphere, Rng};
+    use crate::tuples::{Tuple, Vector};
+
+    #[test]
+    fn same_seed_gives_the_same_sequence() {
+        let mut a = Rng::new(42);
+        let mut b = Rng::new(42);
+        for _ in 0..100 {
+            assert_eq!(a.next_u64(), b.next_u64());
+        }
+    }
+
+    #[test]
+    fn different_seeds_give_different_sequences() {
+        let mut a = Rng::new(1);
+        let mut b = Rng::new(2);
+        assert_ne!(a.next_u64(), b.next_u64());
+
+        let mut c = Rng::for_pixel(3, 4, 7);
+        let mut d = Rng::for_pixel(4, 3, 7);
+        assert_ne!(c.next_u64(), d.next_u64());
+    }
+
+    #[test]
+    fn floats_are_between_zero_and_one() {
+        let mut rng = Rng::new(0);
+        let samples: Vec<f64> = (0..1000).map(|_| rng.next_f64()).collect();
+        assert!(samples.iter().all(|x| (0.0..1.0).contains(x)));
+        let mean = samples.iter().sum::<f64>() / samples.len() as f64;
+        assert!((mean - 0.5).abs() < 0.05);
+    }
+
+    #[test]
+    fn hemisphere_samples_never_point_below_the_surface() {
+        let mut rng = Rng::new(9);
+        let normal = Vector::new(1.0, 1.0, 0.0).normalize();
+        let samples: Vec<Vector> = (0..1000)
+            .map(|_| sample_hemisphere(normal, &mut rng))
+            .collect();
+        for sample in &samples {
+            assert!(float_equal(sample.magnitude(), 1.0));
+            assert!(sample.dot(&normal) >= 0.0);
+        }
+        // Cosine weighting puts the average cosine at 2/3
+        let mean_cos = samples.iter().map(|s| s.dot(&normal)).sum::<f64>() / 1000.0;
+        assert!((mean_cos - 2.0 / 3.0).abs() < 0.05);
+    }
+}
@@ -1,12 +1,14 @@
+use crate::bounds::Bounds;
 use crate::intersections::Intersection;
 use crate::materials::Material;
 use crate::matrices::Matrix4;
 use crate::rays::Ray;
+use crate::transformations::Transform;
 use crate::tuples::{Point, Tuple, Vector};
 
 #[derive(Debug, Copy, Clone, PartialEq)]
 pub struct Sphere {
-    pub transformation: Matrix4,
+    pub transform: Transform,
     pub material: Material,
     pub center: Point,
 }
@@ -14,15 +16,21 @@ pub struct Sphere {
 impl Sphere {
     pub fn new() -> Sphere {
         Sphere {
-            transformation: Matrix4::identity(),
+            transform: Transform::default(),
             material: Material::default(),
             center: Point::new(0.0, 0.0, 0.0),
         }
     }
 
+    pub fn with_transform(transformation: Matrix4) -> Sphere {
+        let mut sphere = Sphere::new();
+        sphere.set_transform(transformation);
+        sphere
+    }
+
     // Returns list of time values where the ray intersects the sphere
     pub fn intersect(&self, ray: Ray) -> Vec<f64> {
-        let ray = ray.transform(self.transformation.inverse().unwrap());
+        let ray = ray.transform(self.transform.inverse());
         let sphere_to_ray = ray.origin - self.center;
         let a = ray.direction.dot(&ray.direction);
         let b = 2.0 * ray.direction.dot(&sphere_to_ray);
@@ -38,16 +46,21 @@ impl Sphere {
     }
 
     pub fn normal_at(&self, point: Point) -> Vector {
-        let object_point = self.transformation.inverse().unwrap() * point;
+        let object_point = self.transform.inverse() * point;
         let object_normal = object_point - Point::new(0.0, 0.0, 0.0);
 
-        let world_normal = self.transformation.inverse().unwrap().transpose() * object_normal;
+        let world_normal = self.transform.inverse().transpose() * object_normal;
 
         world_normal.normalize()
     }
 
+    // Bounds in object space, before the transformation is applied
+    pub fn bounds(&self) -> Bounds {
+        Bounds::new(Point::new(-1.0, -1.0, -1.0), Point::new(1.0, 1.0, 1.0))
+    }
+
     pub fn set_transform(&mut self, transformation: Matrix4) {
-        self.transformation = transformation;
+        self.transform.set_matrix(transformation);
     }
 
     pub fn set_material(&mut self, material: Material) {
@@ -70,6 +83,7 @@ mod tests {
     use crate::rays::Ray;
     use crate::sphere::Sphere;
     use crate::tuples::{Point, Tuple, Vector};
+    use std::f64::consts::FRAC_1_SQRT_2;
 
     #[test]
     fn spheres_should_start_at_the_center() {
@@ -80,15 +94,26 @@ mod tests {
     #[test]
     fn spheres_default_transformation() {
         let sphere = Sphere::new();
-        assert_eq!(sphere.transformation, Matrix4::identity());
+        assert_eq!(sphere.transform.matrix(), Matrix4::identity());
     }
 
     #[test]
     fn changing_sphere_transformation() {
         let mut sphere = Sphere::new();
         let t = Matrix4::translate(2.0, 3.0, 4.0);
-        sphere.set_transform(t.clone());
-        assert_eq!(sphere.transformation, t);
+        sphere.set_transform(t);
+        assert_eq!(sphere.transform.matrix(), t);
+        assert_eq!(
+            sphere.transform.inverse(),
+            Matrix4::translate(-2.0, -3.0, -4.0)
+        );
+    }
+
+    #[test]
+    fn sphere_with_transform_caches_inverse() {
+        let sphere = Sphere::with_transform(Matrix4::scale(2.0, 4.0, 8.0));
+        assert_eq!(sphere.transform.matrix(), Matrix4::scale(2.0, 4.0, 8.0));
+        assert_eq!(sphere.transform.inverse(), Matrix4::scale(0.5, 0.25, 0.125));
     }
 
     #[test]
@@ -221,8 +246,8 @@ mod tests {
     fn compute_normal_on_translated_sphere() {
         let mut sphere = Sphere::new();
         sphere.set_transform(Matrix4::translate(0.0, 1.0, 0.0));
-        let normal = sphere.normal_at(Point::new(0.0, 1.70711, -0.70711));
-        assert_eq!(normal, Vector::new(0.0, 0.70711, -0.70711));
+        let normal = sphere.normal_at(Point::new(0.0, 1.0 + FRAC_1_SQRT_2, -FRAC_1_SQRT_2));
+        assert_eq!(normal, Vector::new(0.0, FRAC_1_SQRT_2, -FRAC_1_SQRT_2));
     }
 
     #[test]
@@ -235,6 +260,21 @@ mod tests {
         assert_eq!(normal, Vector::new(0.0, 0.97014, -0.24254));
     }
 
+    #[test]
+    fn normals_on_a_mirrored_sphere_still_point_outward() {
+        let mut sphere = Sphere::new();
+        sphere.set_transform(Matrix4::translate(3.0, 0.0, 0.0) * Matrix4::scale(-2.0, 1.0, 1.0));
+        let cases = [
+            (Point::new(1.0, 0.0, 0.0), Vector::new(-1.0, 0.0, 0.0)),
+            (Point::new(5.0, 0.0, 0.0), Vector::new(1.0, 0.0, 0.0)),
+            (Point::new(3.0, 1.0, 0.0), Vector::new(0.0, 1.0, 0.0)),
+            (Point::new(3.0, 0.0, -1.0), Vector::new(0.0, 0.0, -1.0)),
+        ];
+        for (point, expected) in cases {
+            assert_eq!(sphere.normal_at(point), expected);
+        }
+    }
+
     #[test]
     fn sphere_has_default_material() {
         let sphere = Sphere::new();
@@ -246,8 +286,18 @@ mod tests {
     fn sphere_can_be_assigned_material() {
         let mut sphere = Sphere::new();
         let material = Material::new(Color::new(0.5, 0.5, 1.0), 0.2, 0.8, 0.8, 90.0);
-        sphere.set_material(material.clone());
+        sphere.set_material(material);
         sphere.material = material;
         assert_eq!(sphere.material, material);
     }
+
+    #[test]
+    fn world_bounds_of_a_transformed_sphere() {
+        let sphere = Object::Sphere(Sphere::with_transform(
+            Matrix4::translate(1.0, -3.0, 5.0) * Matrix4::scale(0.5, 2.0, 4.0),
+        ));
+        let bounds = sphere.world_bounds();
+        assert_eq!(bounds.min, Point::new(0.5, -5.0, 1.0));
+        assert_eq!(bounds.max, Point::new(1.5, -1.0, 9.0));
+    }
 }
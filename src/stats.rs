@@ -0,0 +1,73 @@
+use std::sync::atomic::{AtomicU64, Ordering};
+
+// How much work a render did
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct RenderStats {
+    // Rays traced into the scene to find what they hit
+    pub rays_cast: u64,
+    // Ray-object tests, counting every object checked by every ray
+    pub intersection_tests: u64,
+    // Rays checking whether anything blocks a light or, with ambient occlusion, the sky
+    pub shadow_rays: u64,
+}
+
+// Atomics, so rows rendered in parallel can all count into the same world
+#[derive(Debug, Default)]
+pub struct RenderCounters {
+    rays_cast: AtomicU64,
+    intersection_tests: AtomicU64,
+    shadow_rays: AtomicU64,
+}
+
+impl RenderCounters {
+    pub fn count_ray(&self) {
+        self.rays_cast.fetch_add(1, Ordering::Relaxed);
+    }
+
+    pub fn count_intersection_tests(&self, tests: u64) {
+        self.intersection_tests.fetch_add(tests, Ordering::Relaxed);
+    }
+
+    pub fn count_shadow_ray(&self) {
+        self.shadow_rays.fetch_add(1, Ordering::Relaxed);
+    }
+
+    pub fn snapshot(&self) -> RenderStats {
+        RenderStats {
+            rays_cast: self.rays_cast.load(Ordering::Relaxed),
+            intersection_tests: self.intersection_tests.load(Ordering::Relaxed),
+            shadow_rays: self.shadow_rays.load(Ordering::Relaxed),
+        }
+    }
+
+    pub fn reset(&self) {
+        self.rays_cast.store(0, Ordering::Relaxed);
+        self.intersection_tests.store(0, Ordering::Relaxed);
+        self.shadow_rays.store(0, Ordering::Relaxed);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::stats::{RenderCounters, RenderStats};
+
+    #[test]
+    fn counters_add_up_and_reset() {
+        let counters = RenderCounters::default();
+        counters.count_ray();
+        counters.count_ray();
+        counters.count_intersection_tests(5);
+        counters.count_shadow_ray();
+        assert_eq!(
+            counters.snapshot(),
+            RenderStats {
+                rays_cast: 2,
+                intersection_tests: 5,
+                shadow_rays: 1,
+            }
+        );
+
+        counters.reset();
+        assert_eq!(counters.snapshot(), RenderStats::default());
+    }
+}
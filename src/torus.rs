@@ -0,0 +1,311 @@
+use crate::bounds::Bounds;
+use crate::intersections::Intersection;
+use crate::materials::Material;
+use crate::matrices::Matrix4;
+use crate::rays::Ray;
+use crate::transformations::Transform;
+use crate::tuples::{Point, Tuple, Vector};
+use std::f64::consts::PI;
+
+// Coefficients closer to zero than this are treated as zero by the root solvers
+const SOLVER_EPSILON: f64 = 1e-9;
+
+// A donut lying in the xz plane around the origin. major_radius is the distance from the
+// center to the middle of the tube, minor_radius is the radius of the tube itself.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct Torus {
+    pub major_radius: f64,
+    pub minor_radius: f64,
+    pub transform: Transform,
+    pub material: Material,
+}
+
+impl Torus {
+    pub fn new(major_radius: f64, minor_radius: f64) -> Torus {
+        Torus {
+            major_radius,
+            minor_radius,
+            transform: Transform::default(),
+            material: Material::default(),
+        }
+    }
+
+    // Returns list of time values where the ray intersects the torus. A ray can cross the
+    // surface up to four times, so this means solving a quartic.
+    pub fn intersect(&self, ray: Ray) -> Vec<f64> {
+        let ray = ray.transform(self.transform.inverse());
+        let (o, d) = (ray.origin, ray.direction);
+        let major_squared = self.major_radius.powi(2);
+
+        // Substituting the ray into (x² + y² + z² + R² - r²)² = 4R²(x² + z²)
+        let d_dot_d = d.dot(&d);
+        let o_dot_d = o.x * d.x + o.y * d.y + o.z * d.z;
+        let e = o.x * o.x + o.y * o.y + o.z * o.z + major_squared - self.minor_radius.powi(2);
+        let coefficients = [
+            e * e - 4.0 * major_squared * (o.x * o.x + o.z * o.z),
+            4.0 * o_dot_d * e - 8.0 * major_squared * (o.x * d.x + o.z * d.z),
+            2.0 * d_dot_d * e + 4.0 * o_dot_d * o_dot_d
+                - 4.0 * major_squared * (d.x * d.x + d.z * d.z),
+            4.0 * d_dot_d * o_dot_d,
+            d_dot_d * d_dot_d,
+        ];
+
+        let roots = solve_quartic(coefficients)
+            .into_iter()
+            .map(|t| polish_root(&coefficients, t))
+            .collect();
+        Intersection::sort_intersections(roots)
+    }
+
+    pub fn normal_at(&self, point: Point) -> Vector {
+        let object_point = self.transform.inverse() * point;
+        let (x, y, z) = (object_point.x, object_point.y, object_point.z);
+
+        // Gradient of the implicit torus equation, with the common factor of 4 dropped
+        let s = x * x + y * y + z * z + self.major_radius.powi(2) - self.minor_radius.powi(2);
+        let ring = s - 2.0 * self.major_radius.powi(2);
+        let object_normal = Vector::new(x * ring, y * s, z * ring);
+
+        let world_normal = self.transform.inverse().transpose() * object_normal;
+        world_normal.normalize()
+    }
+
+    pub fn bounds(&self) -> Bounds {
+        let outer = self.major_radius + self.minor_radius;
+        let r = self.minor_radius;
+        Bounds::new(Point::new(-outer, -r, -outer), Point::new(outer, r, outer))
+    }
+
+    pub fn set_transform(&mut self, transformation: Matrix4) {
+        self.transform.set_matrix(transformation);
+    }
+
+    pub fn set_material(&mut self, material: Material) {
+        self.material = material;
+    }
+}
+
+// The solvers below take coefficients lowest power first, so [c, b, a] is c + bx + ax².
+// They follow the closed-form approach from Graphics Gems I ("Roots3And4.c").
+
+fn is_zero(value: f64) -> bool {
+    value.abs() < SOLVER_EPSILON
+}
+
+fn solve_quadratic([c, b, a]: [f64; 3]) -> Vec<f64> {
+    let p = b / (2.0 * a);
+    let q = c / a;
+    let discriminant = p * p - q;
+    if is_zero(discriminant) {
+        vec![-p]
+    } else if discriminant < 0.0 {
+        vec![]
+    } else {
+        let root = discriminant.sqrt();
+        vec![root - p, -root - p]
+    }
+}
+
+fn solve_cubic([d, c, b, a]: [f64; 4]) -> Vec<f64> {
+    // Normalize to x³ + Ax² + Bx + C, then substitute x = y - A/3 to drop the x² term
+    let (a, b, c) = (b / a, c / a, d / a);
+    let p = (-a * a / 3.0 + b) / 3.0;
+    let q = (2.0 / 27.0 * a * a * a - a * b / 3.0 + c) / 2.0;
+    let p_cubed = p * p * p;
+    let discriminant = q * q + p_cubed;
+
+    let roots = if is_zero(discriminant) {
+        if is_zero(q) {
+            vec![0.0]
+        } else {
+            let u = (-q).cbrt();
+            vec![2.0 * u, -u]
+        }
+    } else if discriminant < 0.0 {
+        // Three real roots
+        let phi = (-q / (-p_cubed).sqrt()).clamp(-1.0, 1.0).acos() / 3.0;
+        let t = 2.0 * (-p).sqrt();
+        vec![
+            t * phi.cos(),
+            -t * (phi + PI / 3.0).cos(),
+            -t * (phi - PI / 3.0).cos(),
+        ]
+    } else {
+        let root = discriminant.sqrt();
+        vec![(root - q).cbrt() - (root + q).cbrt()]
+    };
+
+    roots.into_iter().map(|y| y - a / 3.0).collect()
+}
+
+fn solve_quartic([e, d, c, b, a]: [f64; 5]) -> Vec<f64> {
+    // Normalize to x⁴ + Ax³ + Bx² + Cx + D, then substitute x = y - A/4 to drop the x³ term
+    let (a, b, c, d) = (b / a, c / a, d / a, e / a);
+    let a_squared = a * a;
+    let p = -3.0 / 8.0 * a_squared + b;
+    let q = a_squared * a / 8.0 - a * b / 2.0 + c;
+    let r = -3.0 / 256.0 * a_squared * a_squared + a_squared * b / 16.0 - a * c / 4.0 + d;
+
+    let roots = if is_zero(r) {
+        // No constant term, so y = 0 is a root and the rest come from y³ + py + q
+        let mut roots = solve_cubic([q, p, 0.0, 1.0]);
+        roots.push(0.0);
+        roots
+    } else {
+        // Use one root of the resolvent cubic to split into two quadratics
+        let z = solve_cubic([r * p / 2.0 - q * q / 8.0, -r, -p / 2.0, 1.0])[0];
+        let (Some(u), Some(v)) = (checked_sqrt(z * z - r), checked_sqrt(2.0 * z - p)) else {
+            return vec![];
+        };
+        let v = if q < 0.0 { -v } else { v };
+        let mut roots = solve_quadratic([z - u, v, 1.0]);
+        roots.extend(solve_quadratic([z + u, -v, 1.0]));
+        roots
+    };
+
+    roots.into_iter().map(|y| y - a / 4.0).collect()
+}
+
+fn checked_sqrt(value: f64) -> Option<f64> {
+    if is_zero(value) {
+        Some(0.0)
+    } else if value > 0.0 {
+        Some(value.sqrt())
+    } else {
+        None
+    }
+}
+
+// The closed-form roots lose precision when coefficients are large, so tidy them up with a
+// few Newton steps on the original polynomial
+fn polish_root(coefficients: &[f64; 5], mut t: f64) -> f64 {
+    for _ in 0..4 {
+        let (value, slope) = coefficients
+            .iter()
+            .rev()
+            .fold((0.0, 0.0), |(value, slope), c| {
+                (value * t + c, slope * t + value)
+            });
+        if is_zero(slope) {
+            break;
+        }
+        t -= value / slope;
+    }
+    t
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::floats::float_equal;
+    use crate::matrices::Matrix4;
+    use crate::objects::{Intersectable, Object};
+    use crate::rays::Ray;
+    use crate::torus::{solve_cubic, solve_quartic, Torus};
+    use crate::tuples::{Point, Tuple, Vector};
+
+    fn assert_roots(mut actual: Vec<f64>, expected: &[f64]) {
+        actual.sort_by(|a, b| a.partial_cmp(b).unwrap());
+        assert_eq!(actual.len(), expected.len(), "roots were {:?}", actual);
+        for (a, e) in actual.iter().zip(expected) {
+            assert!(float_equal(*a, *e), "roots were {:?}", actual);
+        }
+    }
+
+    #[test]
+    fn solving_cubics() {
+        // (x - 1)(x - 2)(x - 3)
+        assert_roots(solve_cubic([-6.0, 11.0, -6.0, 1.0]), &[1.0, 2.0, 3.0]);
+        // (x - 2)(x² + 1)
+        assert_roots(solve_cubic([-2.0, 1.0, -2.0, 1.0]), &[2.0]);
+    }
+
+    #[test]
+    fn solving_quartics() {
+        // (x - 1)(x - 2)(x - 3)(x - 4)
+        assert_roots(
+            solve_quartic([24.0, -50.0, 35.0, -10.0, 1.0]),
+            &[1.0, 2.0, 3.0, 4.0],
+        );
+        // (x² - 4)(x² + 1)
+        assert_roots(solve_quartic([-4.0, 0.0, -3.0, 0.0, 1.0]), &[-2.0, 2.0]);
+        // x² + 1 squared has no real roots
+        assert_roots(solve_quartic([1.0, 0.0, 2.0, 0.0, 1.0]), &[]);
+    }
+
+    #[test]
+    fn ray_through_both_sides_of_the_tube() {
+        let torus = Torus::new(1.0, 0.3);
+        let ray = Ray::new(Point::new(-5.0, 0.0, 0.0), Vector::new(1.0, 0.0, 0.0));
+        assert_roots(torus.intersect(ray), &[3.7, 4.3, 5.7, 6.3]);
+    }
+
+    #[test]
+    fn ray_down_through_the_tube() {
+        let torus = Torus::new(1.0, 0.3);
+        let ray = Ray::new(Point::new(1.0, 5.0, 0.0), Vector::new(0.0, -1.0, 0.0));
+        assert_roots(torus.intersect(ray), &[4.7, 5.3]);
+    }
+
+    #[test]
+    fn ray_misses_a_torus() {
+        let torus = Torus::new(1.0, 0.3);
+        let cases = [
+            // Straight down the hole in the middle
+            Ray::new(Point::new(0.0, 5.0, 0.0), Vector::new(0.0, -1.0, 0.0)),
+            // Passing above the tube
+            Ray::new(Point::new(0.0, 0.5, -5.0), Vector::new(0.0, 0.0, 1.0)),
+            // Passing outside the ring
+            Ray::new(Point::new(2.0, 0.0, -5.0), Vector::new(0.0, 0.0, 1.0)),
+        ];
+        for ray in cases {
+            assert!(torus.intersect(ray).is_empty());
+        }
+    }
+
+    #[test]
+    fn intersections_lie_on_the_surface() {
+        let torus = Torus::new(2.0, 0.5);
+        let ray = Ray::new(
+            Point::new(-4.0, 1.0, -3.0),
+            Vector::new(1.0, -0.2, 0.7).normalize(),
+        );
+        let intersections = torus.intersect(ray);
+        assert!(!intersections.is_empty());
+        for t in intersections {
+            let point = ray.position(t);
+            let ring_distance = (point.x.powi(2) + point.z.powi(2)).sqrt() - 2.0;
+            let tube_distance = (ring_distance.powi(2) + point.y.powi(2)).sqrt();
+            assert!(float_equal(tube_distance, 0.5));
+        }
+    }
+
+    #[test]
+    fn normals_on_a_torus() {
+        let torus = Torus::new(1.0, 0.3);
+        let cases = [
+            (Point::new(1.3, 0.0, 0.0), Vector::new(1.0, 0.0, 0.0)),
+            (Point::new(0.7, 0.0, 0.0), Vector::new(-1.0, 0.0, 0.0)),
+            (Point::new(-0.7, 0.0, 0.0), Vector::new(1.0, 0.0, 0.0)),
+            (Point::new(0.0, 0.3, 1.0), Vector::new(0.0, 1.0, 0.0)),
+            (Point::new(0.0, 0.0, -1.3), Vector::new(0.0, 0.0, -1.0)),
+        ];
+        for (point, expected) in cases {
+            assert_eq!(torus.normal_at(point), expected);
+        }
+    }
+
+    #[test]
+    fn intersecting_a_transformed_torus() {
+        let mut torus = Object::torus(1.0, 0.3);
+        torus.set_transform(Matrix4::translate(0.0, 2.0, 0.0));
+        let ray = Ray::new(Point::new(-5.0, 2.0, 0.0), Vector::new(1.0, 0.0, 0.0));
+        assert_roots(torus.intersect(ray), &[3.7, 4.3, 5.7, 6.3]);
+    }
+
+    #[test]
+    fn torus_bounds() {
+        let bounds = Torus::new(1.0, 0.3).bounds();
+        assert_eq!(bounds.min, Point::new(-1.3, -0.3, -1.3));
+        assert_eq!(bounds.max, Point::new(1.3, 0.3, 1.3));
+    }
+}
@@ -1,4 +1,5 @@
-use crate::matrices::Matrix4;
+use crate::bounds::Bounds;
+use crate::matrices::{Matrix4, MatrixError};
 use crate::tuples::{Point, Vector};
 
 pub fn view_transform(from: Point, to: Point, up: Vector) -> Matrix4 {
@@ -16,11 +17,166 @@ pub fn view_transform(from: Point, to: Point, up: Vector) -> Matrix4 {
     orientation * Matrix4::translate(-from.x, -from.y, -from.z)
 }
 
+// A shape's transformation along with its inverse, worked out once here rather than for
+// every ray. The fields are private so the two can't get out of step.
+#[derive(Debug, Clone, Copy)]
+pub struct Transform {
+    matrix: Matrix4,
+    // The error is kept for matrices that can't be inverted, e.g. a scale of zero on one
+    // axis. Such a transform is degenerate: it squashes things flat.
+    inverse: Result<Matrix4, MatrixError>,
+    // Where the matrix ends up at time 1.0, for motion blur
+    motion: Option<Matrix4>,
+    // The shape's bounds in world space, saved by Object for culling. Cleared whenever the
+    // matrix or motion changes, so it's never out of date with them.
+    world_bounds: Option<Bounds>,
+}
+
+impl Transform {
+    pub fn new(matrix: Matrix4) -> Transform {
+        Transform {
+            matrix,
+            inverse: matrix.try_inverse(),
+            motion: None,
+            world_bounds: None,
+        }
+    }
+
+    pub fn matrix(&self) -> Matrix4 {
+        self.matrix
+    }
+
+    // The zero matrix when the transform is degenerate
+    pub fn inverse(&self) -> Matrix4 {
+        self.inverse.unwrap_or_default()
+    }
+
+    pub fn try_inverse(&self) -> Result<Matrix4, MatrixError> {
+        self.inverse
+    }
+
+    pub fn is_degenerate(&self) -> bool {
+        self.inverse.is_err()
+    }
+
+    // Replaces the matrix, keeping any motion
+    pub fn set_matrix(&mut self, matrix: Matrix4) {
+        *self = Transform {
+            motion: self.motion,
+            ..Transform::new(matrix)
+        };
+    }
+
+    pub fn motion(&self) -> Option<Matrix4> {
+        self.motion
+    }
+
+    pub fn set_motion(&mut self, motion: Matrix4) {
+        self.motion = Some(motion);
+        self.world_bounds = None;
+    }
+
+    // Where local, a shape's bounds in its own space, ends up in world space. Moving shapes
+    // are bounded over their whole path.
+    pub fn bounds_in_world(&self, local: Bounds) -> Bounds {
+        let bounds = local.transform(self.matrix);
+        match self.motion {
+            Some(motion) => bounds.merge(&local.transform(motion)),
+            None => bounds,
+        }
+    }
+
+    pub fn save_world_bounds(&mut self, local: Bounds) {
+        self.world_bounds = Some(self.bounds_in_world(local));
+    }
+
+    pub fn saved_world_bounds(&self) -> Option<Bounds> {
+        self.world_bounds
+    }
+
+    // Maps world space at the given time back to where the matrix puts things at time 0.0.
+    // None when there's no motion.
+    pub fn motion_at(&self, time: f64) -> Option<Matrix4> {
+        let motion = self.motion?;
+        let current = self.matrix.lerp(&motion, time);
+        Some(self.matrix * current.try_inverse().ok()?)
+    }
+}
+
+// The saved world bounds are left out, since they only depend on the rest
+impl PartialEq for Transform {
+    fn eq(&self, other: &Self) -> bool {
+        self.matrix == other.matrix && self.inverse == other.inverse && self.motion == other.motion
+    }
+}
+
+impl Default for Transform {
+    fn default() -> Self {
+        Transform::new(Matrix4::identity())
+    }
+}
+
+// Chains transformations in the order they happen, rather than the reverse order
+// matrices have to be multiplied in
+#[derive(Debug, Clone, Copy)]
+pub struct TransformBuilder {
+    matrix: Matrix4,
+}
+
+impl TransformBuilder {
+    pub fn new() -> TransformBuilder {
+        TransformBuilder {
+            matrix: Matrix4::identity(),
+        }
+    }
+
+    pub fn then(self, transformation: Matrix4) -> TransformBuilder {
+        TransformBuilder {
+            matrix: transformation * self.matrix,
+        }
+    }
+
+    pub fn translate(self, x: f64, y: f64, z: f64) -> TransformBuilder {
+        self.then(Matrix4::translate(x, y, z))
+    }
+
+    pub fn scale(self, x: f64, y: f64, z: f64) -> TransformBuilder {
+        self.then(Matrix4::scale(x, y, z))
+    }
+
+    pub fn rotate_x(self, r: f64) -> TransformBuilder {
+        self.then(Matrix4::rotate_x(r))
+    }
+
+    pub fn rotate_y(self, r: f64) -> TransformBuilder {
+        self.then(Matrix4::rotate_y(r))
+    }
+
+    pub fn rotate_z(self, r: f64) -> TransformBuilder {
+        self.then(Matrix4::rotate_z(r))
+    }
+
+    pub fn shear(self, xy: f64, xz: f64, yx: f64, yz: f64, zx: f64, zy: f64) -> TransformBuilder {
+        self.then(Matrix4::shear(xy, xz, yx, yz, zx, zy))
+    }
+
+    pub fn build(self) -> Matrix4 {
+        self.matrix
+    }
+}
+
+impl Default for TransformBuilder {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 #[cfg(test)]
 mod tests {
-    use crate::matrices::Matrix4;
-    use crate::transformations::view_transform;
+    use crate::matrices::{Matrix4, MatrixError};
+    use crate::transformations::{view_transform, Transform, TransformBuilder};
     use crate::tuples::{Point, Tuple, Vector};
+    use std::f64::consts::PI;
 
     #[test]
     fn transformation_matrix_for_default_orientation() {
@@ -64,4 +220,54 @@ mod tests {
         let expected_matrix = Matrix4::new(expected_data);
         assert_eq!(transformation, expected_matrix);
     }
+
+    #[test]
+    fn builder_applies_transformations_in_call_order() {
+        let a = Matrix4::rotate_x(PI / 2.0);
+        let b = Matrix4::scale(5.0, 5.0, 5.0);
+        let c = Matrix4::translate(10.0, 5.0, 7.0);
+        let transformation = TransformBuilder::new()
+            .rotate_x(PI / 2.0)
+            .scale(5.0, 5.0, 5.0)
+            .translate(10.0, 5.0, 7.0)
+            .build();
+        assert_eq!(transformation, c * b * a);
+        let point = Point::new(1.0, 0.0, 1.0);
+        assert_eq!(transformation * point, Point::new(15.0, 0.0, 7.0));
+    }
+
+    #[test]
+    fn empty_builder_is_the_identity() {
+        assert_eq!(TransformBuilder::new().build(), Matrix4::identity());
+    }
+
+    #[test]
+    fn transform_caches_its_inverse() {
+        let transform = Transform::new(Matrix4::scale(2.0, 4.0, 8.0));
+        assert_eq!(transform.matrix(), Matrix4::scale(2.0, 4.0, 8.0));
+        assert_eq!(transform.inverse(), Matrix4::scale(0.5, 0.25, 0.125));
+        assert_eq!(Transform::default().inverse(), Matrix4::identity());
+    }
+
+    #[test]
+    fn changing_the_matrix_keeps_the_motion() {
+        let mut transform = Transform::default();
+        transform.set_motion(Matrix4::translate(2.0, 0.0, 0.0));
+        transform.set_matrix(Matrix4::translate(0.0, 1.0, 0.0));
+        assert_eq!(transform.inverse(), Matrix4::translate(0.0, -1.0, 0.0));
+        assert_eq!(transform.motion(), Some(Matrix4::translate(2.0, 0.0, 0.0)));
+        assert_eq!(
+            transform.motion_at(0.5),
+            Some(Matrix4::translate(-1.0, 0.5, 0.0))
+        );
+        assert_eq!(Transform::default().motion_at(0.5), None);
+    }
+
+    #[test]
+    fn a_transform_that_cannot_be_inverted_is_degenerate() {
+        let transform = Transform::new(Matrix4::scale(1.0, 0.0, 1.0));
+        assert!(transform.is_degenerate());
+        assert_eq!(transform.try_inverse(), Err(MatrixError::NotInvertible));
+        assert!(!Transform::default().is_degenerate());
+    }
 }
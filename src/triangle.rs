@@ -0,0 +1,322 @@
+use crate::bounds::Bounds;
+use crate::floats::EPSILON;
+use crate::materials::Material;
+use crate::matrices::Matrix4;
+use crate::rays::Ray;
+use crate::transformations::Transform;
+use crate::tuples::{Point, Vector};
+
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct Triangle {
+    pub p1: Point,
+    pub p2: Point,
+    pub p3: Point,
+    pub e1: Vector,
+    pub e2: Vector,
+    pub normal: Vector,
+    pub transform: Transform,
+    pub material: Material,
+}
+
+impl Triangle {
+    pub fn new(p1: Point, p2: Point, p3: Point) -> Triangle {
+        let e1 = p2 - p1;
+        let e2 = p3 - p1;
+        Triangle {
+            p1,
+            p2,
+            p3,
+            e1,
+            e2,
+            normal: e2.cross(&e1).normalize(),
+            transform: Transform::default(),
+            material: Material::default(),
+        }
+    }
+
+    // Returns list of time values where the ray intersects the triangle
+    pub fn intersect(&self, ray: Ray) -> Vec<f64> {
+        self.intersect_with_uv(ray)
+            .iter()
+            .map(|(t, _, _)| *t)
+            .collect()
+    }
+
+    // Returns (t, u, v) for the hit, if any
+    pub fn intersect_with_uv(&self, ray: Ray) -> Option<(f64, f64, f64)> {
+        let ray = ray.transform(self.transform.inverse());
+        moller_trumbore(self.p1, self.e1, self.e2, ray)
+    }
+
+    // The normal is the same everywhere on a flat triangle
+    pub fn normal_at(&self, _point: Point) -> Vector {
+        let world_normal = self.transform.inverse().transpose() * self.normal;
+        world_normal.normalize()
+    }
+
+    pub fn bounds(&self) -> Bounds {
+        let mut bounds = Bounds::empty();
+        bounds.add_point(self.p1);
+        bounds.add_point(self.p2);
+        bounds.add_point(self.p3);
+        bounds
+    }
+
+    pub fn set_transform(&mut self, transformation: Matrix4) {
+        self.transform.set_matrix(transformation);
+    }
+
+    pub fn set_material(&mut self, material: Material) {
+        self.material = material;
+    }
+}
+
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct SmoothTriangle {
+    pub p1: Point,
+    pub p2: Point,
+    pub p3: Point,
+    pub n1: Vector,
+    pub n2: Vector,
+    pub n3: Vector,
+    pub e1: Vector,
+    pub e2: Vector,
+    pub transform: Transform,
+    pub material: Material,
+}
+
+impl SmoothTriangle {
+    pub fn new(
+        p1: Point,
+        p2: Point,
+        p3: Point,
+        n1: Vector,
+        n2: Vector,
+        n3: Vector,
+    ) -> SmoothTriangle {
+        SmoothTriangle {
+            p1,
+            p2,
+            p3,
+            n1,
+            n2,
+            n3,
+            e1: p2 - p1,
+            e2: p3 - p1,
+            transform: Transform::default(),
+            material: Material::default(),
+        }
+    }
+
+    pub fn intersect(&self, ray: Ray) -> Vec<f64> {
+        self.intersect_with_uv(ray)
+            .iter()
+            .map(|(t, _, _)| *t)
+            .collect()
+    }
+
+    pub fn intersect_with_uv(&self, ray: Ray) -> Option<(f64, f64, f64)> {
+        let ray = ray.transform(self.transform.inverse());
+        moller_trumbore(self.p1, self.e1, self.e2, ray)
+    }
+
+    // Without a hit to interpolate from, fall back to the vertex normals' average
+    pub fn normal_at(&self, point: Point) -> Vector {
+        self.normal_at_uv(point, 1.0 / 3.0, 1.0 / 3.0)
+    }
+
+    // Interpolates the vertex normals using the barycentric u/v of the hit
+    pub fn normal_at_uv(&self, _point: Point, u: f64, v: f64) -> Vector {
+        let object_normal = self.n2 * u + self.n3 * v + self.n1 * (1.0 - u - v);
+        let world_normal = self.transform.inverse().transpose() * object_normal;
+        world_normal.normalize()
+    }
+
+    pub fn bounds(&self) -> Bounds {
+        let mut bounds = Bounds::empty();
+        bounds.add_point(self.p1);
+        bounds.add_point(self.p2);
+        bounds.add_point(self.p3);
+        bounds
+    }
+
+    pub fn set_transform(&mut self, transformation: Matrix4) {
+        self.transform.set_matrix(transformation);
+    }
+
+    pub fn set_material(&mut self, material: Material) {
+        self.material = material;
+    }
+}
+
+// Möller–Trumbore ray/triangle intersection, with the ray already in object space
+fn moller_trumbore(p1: Point, e1: Vector, e2: Vector, ray: Ray) -> Option<(f64, f64, f64)> {
+    let dir_cross_e2 = ray.direction.cross(&e2);
+    let determinant = e1.dot(&dir_cross_e2);
+
+    // Ray is parallel to the triangle
+    if determinant.abs() < EPSILON {
+        return None;
+    }
+
+    let f = 1.0 / determinant;
+    let p1_to_origin = ray.origin - p1;
+    let u = f * p1_to_origin.dot(&dir_cross_e2);
+    if !(0.0..=1.0).contains(&u) {
+        return None;
+    }
+
+    let origin_cross_e1 = p1_to_origin.cross(&e1);
+    let v = f * ray.direction.dot(&origin_cross_e1);
+    if v < 0.0 || (u + v) > 1.0 {
+        return None;
+    }
+
+    let t = f * e2.dot(&origin_cross_e1);
+    Some((t, u, v))
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::intersections::Intersection;
+    use crate::matrices::Matrix4;
+    use crate::objects::{Intersectable, Object};
+    use crate::rays::Ray;
+    use crate::triangle::{SmoothTriangle, Triangle};
+    use crate::tuples::{Point, Tuple, Vector};
+
+    fn default_triangle() -> Triangle {
+        Triangle::new(
+            Point::new(0.0, 1.0, 0.0),
+            Point::new(-1.0, 0.0, 0.0),
+            Point::new(1.0, 0.0, 0.0),
+        )
+    }
+
+    fn default_smooth_triangle() -> SmoothTriangle {
+        SmoothTriangle::new(
+            Point::new(0.0, 1.0, 0.0),
+            Point::new(-1.0, 0.0, 0.0),
+            Point::new(1.0, 0.0, 0.0),
+            Vector::new(0.0, 1.0, 0.0),
+            Vector::new(-1.0, 0.0, 0.0),
+            Vector::new(1.0, 0.0, 0.0),
+        )
+    }
+
+    #[test]
+    fn constructing_a_triangle() {
+        let triangle = default_triangle();
+        assert_eq!(triangle.e1, Vector::new(-1.0, -1.0, 0.0));
+        assert_eq!(triangle.e2, Vector::new(1.0, -1.0, 0.0));
+        assert_eq!(triangle.normal, Vector::new(0.0, 0.0, -1.0));
+    }
+
+    #[test]
+    fn finding_normal_on_a_triangle() {
+        let triangle = default_triangle();
+        let n1 = triangle.normal_at(Point::new(0.0, 0.5, 0.0));
+        let n2 = triangle.normal_at(Point::new(-0.5, 0.75, 0.0));
+        let n3 = triangle.normal_at(Point::new(0.5, 0.25, 0.0));
+        assert_eq!(n1, triangle.normal);
+        assert_eq!(n2, triangle.normal);
+        assert_eq!(n3, triangle.normal);
+    }
+
+    #[test]
+    fn intersecting_ray_parallel_to_triangle() {
+        let triangle = default_triangle();
+        let ray = Ray::new(Point::new(0.0, -1.0, -2.0), Vector::new(0.0, 1.0, 0.0));
+        assert_eq!(triangle.intersect(ray).len(), 0);
+    }
+
+    #[test]
+    fn ray_misses_p1_p3_edge() {
+        let triangle = default_triangle();
+        let ray = Ray::new(Point::new(1.0, 1.0, -2.0), Vector::new(0.0, 0.0, 1.0));
+        assert_eq!(triangle.intersect(ray).len(), 0);
+    }
+
+    #[test]
+    fn ray_misses_p1_p2_edge() {
+        let triangle = default_triangle();
+        let ray = Ray::new(Point::new(-1.0, 1.0, -2.0), Vector::new(0.0, 0.0, 1.0));
+        assert_eq!(triangle.intersect(ray).len(), 0);
+    }
+
+    #[test]
+    fn ray_misses_p2_p3_edge() {
+        let triangle = default_triangle();
+        let ray = Ray::new(Point::new(0.0, -1.0, -2.0), Vector::new(0.0, 0.0, 1.0));
+        assert_eq!(triangle.intersect(ray).len(), 0);
+    }
+
+    #[test]
+    fn ray_strikes_a_triangle() {
+        let triangle = default_triangle();
+        let ray = Ray::new(Point::new(0.0, 0.5, -2.0), Vector::new(0.0, 0.0, 1.0));
+        let intersections = triangle.intersect(ray);
+        assert_eq!(intersections.len(), 1);
+        assert_eq!(intersections[0], 2.0);
+    }
+
+    #[test]
+    fn intersection_with_smooth_triangle_stores_u_and_v() {
+        let triangle = Object::SmoothTriangle(default_smooth_triangle());
+        let ray = Ray::new(Point::new(-0.2, 0.3, -2.0), Vector::new(0.0, 0.0, 1.0));
+        let intersections = triangle.intersect_with_object(ray);
+        let (u, v) = intersections[0].uv.unwrap();
+        assert!((u - 0.45).abs() < 0.0001);
+        assert!((v - 0.25).abs() < 0.0001);
+    }
+
+    #[test]
+    fn smooth_triangle_uses_u_and_v_to_interpolate_normal() {
+        let triangle = Object::SmoothTriangle(default_smooth_triangle());
+        let intersection = Intersection::with_uv(1.0, &triangle, 0.45, 0.25);
+        let normal = triangle.normal_at_hit(Point::new(0.0, 0.0, 0.0), &intersection);
+        assert_eq!(normal, Vector::new(-0.5547, 0.83205, 0.0));
+    }
+
+    #[test]
+    fn preparing_normal_on_smooth_triangle() {
+        let triangle = Object::SmoothTriangle(default_smooth_triangle());
+        let intersection = Intersection::with_uv(1.0, &triangle, 0.45, 0.25);
+        let ray = Ray::new(Point::new(-0.2, 0.3, -2.0), Vector::new(0.0, 0.0, 1.0));
+        let computations = intersection.prepare_computations(ray);
+        assert_eq!(
+            computations.normal_vector,
+            Vector::new(-0.5547, 0.83205, 0.0)
+        );
+    }
+
+    #[test]
+    fn triangle_can_be_built_from_object() {
+        let object = Object::triangle(
+            Point::new(0.0, 1.0, 0.0),
+            Point::new(-1.0, 0.0, 0.0),
+            Point::new(1.0, 0.0, 0.0),
+        );
+        assert_eq!(object, Object::Triangle(default_triangle()));
+    }
+
+    #[test]
+    fn intersecting_a_transformed_triangle() {
+        let mut triangle = Object::Triangle(default_triangle());
+        triangle.set_transform(Matrix4::translate(0.0, 0.0, 1.0));
+        let ray = Ray::new(Point::new(0.0, 0.5, -2.0), Vector::new(0.0, 0.0, 1.0));
+        assert_eq!(triangle.intersect(ray), vec![3.0]);
+    }
+
+    #[test]
+    fn triangle_bounds_fit_its_points() {
+        let triangle = Triangle::new(
+            Point::new(-3.0, 7.0, 2.0),
+            Point::new(6.0, 2.0, -4.0),
+            Point::new(2.0, -1.0, -1.0),
+        );
+        let bounds = triangle.bounds();
+        assert_eq!(bounds.min, Point::new(-3.0, -1.0, -4.0));
+        assert_eq!(bounds.max, Point::new(6.0, 7.0, 2.0));
+    }
+}
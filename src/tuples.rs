@@ -42,6 +42,75 @@ impl Vector {
     pub fn dot(&self, other: &Vector) -> f64 {
         self.x * other.x + self.y * other.y + self.z * other.z
     }
+
+    // In radians, from 0 to PI
+    pub fn angle_between(&self, other: &Vector) -> f64 {
+        let cos = self.dot(other) / (self.magnitude() * other.magnitude());
+        // Rounding can push cos just outside [-1, 1], which would make acos return NaN
+        cos.clamp(-1.0, 1.0).acos()
+    }
+
+    // The part of this vector that points along other
+    pub fn project_onto(&self, other: &Vector) -> Vector {
+        *other * (self.dot(other) / other.dot(other))
+    }
+
+    // The part of this vector perpendicular to other
+    pub fn reject_from(&self, other: &Vector) -> Vector {
+        *self - self.project_onto(other)
+    }
+
+    // Blends from self at t = 0.0 to other at t = 1.0, with t clamped to that range
+    pub fn lerp(&self, other: &Vector, t: f64) -> Vector {
+        let t = t.clamp(0.0, 1.0);
+        *self + (*other - *self) * t
+    }
+
+    // Two unit vectors perpendicular to this one and each other, followed by this one
+    // normalized, so directions around a normal can be built as tangent/bitangent/normal
+    pub fn orthonormal_basis(&self) -> (Vector, Vector, Vector) {
+        let normal = self.normalize();
+        // Start from whichever axis is least parallel to the normal
+        let helper = if normal.x.abs() < 0.9 {
+            Vector::new(1.0, 0.0, 0.0)
+        } else {
+            Vector::new(0.0, 1.0, 0.0)
+        };
+        let tangent = helper.cross(&normal).normalize();
+        let bitangent = normal.cross(&tangent);
+        (tangent, bitangent, normal)
+    }
+
+    // Component-wise minimum
+    pub fn min(&self, other: &Vector) -> Vector {
+        Vector::new(
+            self.x.min(other.x),
+            self.y.min(other.y),
+            self.z.min(other.z),
+        )
+    }
+
+    // Component-wise maximum
+    pub fn max(&self, other: &Vector) -> Vector {
+        Vector::new(
+            self.x.max(other.x),
+            self.y.max(other.y),
+            self.z.max(other.z),
+        )
+    }
+
+    pub fn abs(&self) -> Vector {
+        Vector::new(self.x.abs(), self.y.abs(), self.z.abs())
+    }
+}
+
+impl Vector {
+    // Like ==, but with a tolerance of epsilon rather than the fixed EPSILON
+    pub fn approx_eq(&self, other: &Vector, epsilon: f64) -> bool {
+        floats::approx_equal(self.x, other.x, epsilon)
+            && floats::approx_equal(self.y, other.y, epsilon)
+            && floats::approx_equal(self.z, other.z, epsilon)
+    }
 }
 
 impl Tuple for Vector {
@@ -110,9 +179,7 @@ impl Div<f64> for Vector {
 
 impl PartialEq for Vector {
     fn eq(&self, other: &Self) -> bool {
-        floats::float_equal(self.x, other.x)
-            && floats::float_equal(self.y, other.y)
-            && floats::float_equal(self.z, other.z)
+        self.approx_eq(other, floats::EPSILON)
     }
 }
 
@@ -123,6 +190,57 @@ pub struct Point {
     pub z: f64,
 }
 
+impl Point {
+    // Moves from self at t = 0.0 to other at t = 1.0, with t clamped to that range
+    pub fn lerp(&self, other: &Point, t: f64) -> Point {
+        let t = t.clamp(0.0, 1.0);
+        *self + (*other - *self) * t
+    }
+
+    pub fn distance(&self, other: &Point) -> f64 {
+        (*other - *self).magnitude()
+    }
+
+    // Cheaper than distance when only comparing which point is closer
+    pub fn distance_squared(&self, other: &Point) -> f64 {
+        let v = *other - *self;
+        v.dot(&v)
+    }
+
+    pub fn midpoint(&self, other: &Point) -> Point {
+        self.lerp(other, 0.5)
+    }
+
+    // Like ==, but with a tolerance of epsilon rather than the fixed EPSILON
+    pub fn approx_eq(&self, other: &Point, epsilon: f64) -> bool {
+        floats::approx_equal(self.x, other.x, epsilon)
+            && floats::approx_equal(self.y, other.y, epsilon)
+            && floats::approx_equal(self.z, other.z, epsilon)
+    }
+
+    // Component-wise minimum
+    pub fn min(&self, other: &Point) -> Point {
+        Point::new(
+            self.x.min(other.x),
+            self.y.min(other.y),
+            self.z.min(other.z),
+        )
+    }
+
+    // Component-wise maximum
+    pub fn max(&self, other: &Point) -> Point {
+        Point::new(
+            self.x.max(other.x),
+            self.y.max(other.y),
+            self.z.max(other.z),
+        )
+    }
+
+    pub fn abs(&self) -> Point {
+        Point::new(self.x.abs(), self.y.abs(), self.z.abs())
+    }
+}
+
 impl Tuple for Point {
     fn new(x: f64, y: f64, z: f64) -> Point {
         Point { x, y, z }
@@ -205,9 +323,7 @@ impl Div<f64> for Point {
 
 impl PartialEq for Point {
     fn eq(&self, other: &Self) -> bool {
-        floats::float_equal(self.x, other.x)
-            && floats::float_equal(self.y, other.y)
-            && floats::float_equal(self.z, other.z)
+        self.approx_eq(other, floats::EPSILON)
     }
 }
 
@@ -215,6 +331,7 @@ impl PartialEq for Point {
 mod tests {
     use crate::floats::float_equal;
     use crate::tuples::{Point, Tuple, Vector};
+    use std::f64::consts::PI;
 
     #[test]
     fn a_tuple_with_1_is_a_point() {
@@ -423,4 +540,139 @@ mod tests {
         let reflect = vector.reflect(&normal);
         assert_eq!(reflect, Vector::new(1.0, 0.0, 0.0));
     }
+
+    #[test]
+    fn angle_between_vectors() {
+        let x = Vector::new(1.0, 0.0, 0.0);
+        assert!(float_equal(
+            x.angle_between(&Vector::new(0.0, 3.0, 0.0)),
+            PI / 2.0
+        ));
+        assert!(float_equal(
+            x.angle_between(&Vector::new(2.0, 0.0, 0.0)),
+            0.0
+        ));
+        assert!(float_equal(
+            x.angle_between(&Vector::new(-5.0, 0.0, 0.0)),
+            PI
+        ));
+        assert!(float_equal(
+            x.angle_between(&Vector::new(1.0, 1.0, 0.0)),
+            PI / 4.0
+        ));
+    }
+
+    #[test]
+    fn angle_between_nearly_parallel_vectors_is_not_nan() {
+        let v = Vector::new(0.1, 0.2, 0.3);
+        assert!(float_equal(v.angle_between(&(v * 3.0)), 0.0));
+    }
+
+    #[test]
+    fn projecting_and_rejecting_vectors() {
+        let v = Vector::new(3.0, 4.0, 5.0);
+        let x = Vector::new(2.0, 0.0, 0.0);
+        assert_eq!(v.project_onto(&x), Vector::new(3.0, 0.0, 0.0));
+        assert_eq!(v.reject_from(&x), Vector::new(0.0, 4.0, 5.0));
+    }
+
+    #[test]
+    fn projecting_onto_parallel_and_orthogonal_vectors() {
+        let v = Vector::new(1.0, 2.0, 0.0);
+        let parallel = Vector::new(-2.0, -4.0, 0.0);
+        let orthogonal = Vector::new(0.0, 0.0, 1.0);
+        assert_eq!(v.project_onto(&parallel), v);
+        assert_eq!(v.reject_from(&parallel), Vector::zero());
+        assert_eq!(v.project_onto(&orthogonal), Vector::zero());
+        assert_eq!(v.reject_from(&orthogonal), v);
+    }
+
+    #[test]
+    fn interpolating_between_vectors() {
+        let from = Vector::new(1.0, 0.0, 0.0);
+        let to = Vector::new(0.0, 2.0, -4.0);
+        assert_eq!(from.lerp(&to, 0.0), from);
+        assert_eq!(from.lerp(&to, 0.5), Vector::new(0.5, 1.0, -2.0));
+        assert_eq!(from.lerp(&to, 1.0), to);
+        assert_eq!(from.lerp(&to, 1.5), to);
+    }
+
+    #[test]
+    fn interpolating_between_points() {
+        let from = Point::new(-2.0, 0.0, 4.0);
+        let to = Point::new(2.0, 1.0, 0.0);
+        assert_eq!(from.lerp(&to, 0.0), from);
+        assert_eq!(from.lerp(&to, 0.5), Point::new(0.0, 0.5, 2.0));
+        assert_eq!(from.lerp(&to, 1.0), to);
+        assert_eq!(from.lerp(&to, -0.5), from);
+    }
+
+    #[test]
+    fn distance_between_points() {
+        let a = Point::new(1.0, 2.0, 3.0);
+        let b = Point::new(4.0, 6.0, 3.0);
+        assert_eq!(a.distance(&b), 5.0);
+        assert_eq!(b.distance(&a), 5.0);
+        assert_eq!(a.distance_squared(&b), 25.0);
+        assert_eq!(a.distance(&a), 0.0);
+    }
+
+    #[test]
+    fn midpoint_between_points() {
+        let a = Point::new(-1.0, 2.0, 3.0);
+        let b = Point::new(3.0, -2.0, 4.0);
+        assert_eq!(a.midpoint(&b), Point::new(1.0, 0.0, 3.5));
+    }
+
+    #[test]
+    fn comparing_tuples_with_a_custom_tolerance() {
+        let a = Point::new(1.0, 2.0, 3.0);
+        let b = Point::new(1.0, 2.0, 3.000001);
+        assert!(a.approx_eq(&b, 1e-5));
+        assert!(!a.approx_eq(&b, 1e-9));
+
+        let v = Vector::new(0.0, 1.0, 0.0);
+        let w = Vector::new(0.01, 1.0, 0.0);
+        assert!(v.approx_eq(&w, 0.1));
+        assert!(!v.approx_eq(&w, 0.001));
+    }
+
+    #[test]
+    fn building_an_orthonormal_basis() {
+        for vector in [
+            Vector::new(0.0, 0.0, 2.0),
+            Vector::new(1.0, 0.0, 0.0),
+            Vector::new(1.0, -2.0, 3.0),
+        ] {
+            let (tangent, bitangent, normal) = vector.orthonormal_basis();
+            assert_eq!(normal, vector.normalize());
+            for axis in [tangent, bitangent] {
+                assert!(float_equal(axis.magnitude(), 1.0));
+                assert!(float_equal(axis.dot(&normal), 0.0));
+            }
+            assert!(float_equal(tangent.dot(&bitangent), 0.0));
+        }
+    }
+
+    #[test]
+    fn component_wise_min_max_and_abs() {
+        let a = Vector::new(1.0, -4.0, 3.0);
+        let b = Vector::new(-2.0, 5.0, 3.0);
+        assert_eq!(a.min(&b), Vector::new(-2.0, -4.0, 3.0));
+        assert_eq!(a.max(&b), Vector::new(1.0, 5.0, 3.0));
+        assert_eq!(a.abs(), Vector::new(1.0, 4.0, 3.0));
+
+        let p = Point::new(-3.0, 0.5, 7.0);
+        let q = Point::new(2.0, -1.0, 1.0);
+        assert_eq!(p.min(&q), Point::new(-3.0, -1.0, 1.0));
+        assert_eq!(p.max(&q), Point::new(2.0, 0.5, 7.0));
+        assert_eq!(p.abs(), Point::new(3.0, 0.5, 7.0));
+    }
+
+    #[test]
+    fn clamping_a_point_inside_a_box() {
+        let (min, max) = (Point::new(-1.0, -1.0, -1.0), Point::new(1.0, 1.0, 1.0));
+        let point = Point::new(5.0, 0.5, -3.0);
+        assert_eq!(point.max(&min).min(&max), Point::new(1.0, 0.5, -1.0));
+    }
 }
@@ -0,0 +1,173 @@
+use crate::tuples::Point;
+use std::f64::consts::PI;
+
+// Functions that turn a point on a shape into (u, v) texture coordinates, each from 0.0 to 1.0
+
+// For a sphere around the origin. u goes once around the equator, starting from -z, and v
+// goes from the bottom pole up to the top one.
+pub fn spherical_map(point: Point) -> (f64, f64) {
+    let theta = point.x.atan2(point.z);
+    let radius = (point.x * point.x + point.y * point.y + point.z * point.z).sqrt();
+    let phi = (point.y / radius).clamp(-1.0, 1.0).acos();
+    let raw_u = theta / (2.0 * PI);
+    (1.0 - (raw_u + 0.5), 1.0 - phi / PI)
+}
+
+// For the xz plane, repeating every unit
+pub fn planar_map(point: Point) -> (f64, f64) {
+    (point.x.rem_euclid(1.0), point.z.rem_euclid(1.0))
+}
+
+// For a cylinder around the y axis. u goes around it like spherical_map, and v repeats
+// every unit of height.
+pub fn cylindrical_map(point: Point) -> (f64, f64) {
+    let theta = point.x.atan2(point.z);
+    let raw_u = theta / (2.0 * PI);
+    (1.0 - (raw_u + 0.5), point.y.rem_euclid(1.0))
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CubeFace {
+    Left,
+    Right,
+    Front,
+    Back,
+    Up,
+    Down,
+}
+
+// For the cube from -1 to 1 on each axis. Each face gets its own full (u, v) square, seen
+// from outside the cube.
+pub fn cube_map(point: Point) -> (CubeFace, f64, f64) {
+    let face = cube_face(point);
+    let (u, v) = match face {
+        CubeFace::Front => (point.x + 1.0, point.y + 1.0),
+        CubeFace::Back => (1.0 - point.x, point.y + 1.0),
+        CubeFace::Left => (point.z + 1.0, point.y + 1.0),
+        CubeFace::Right => (1.0 - point.z, point.y + 1.0),
+        CubeFace::Up => (point.x + 1.0, 1.0 - point.z),
+        CubeFace::Down => (point.x + 1.0, point.z + 1.0),
+    };
+    (face, u.rem_euclid(2.0) / 2.0, v.rem_euclid(2.0) / 2.0)
+}
+
+// The face is picked by whichever coordinate is furthest from the center
+fn cube_face(point: Point) -> CubeFace {
+    let coord = point.x.abs().max(point.y.abs()).max(point.z.abs());
+    if coord == point.x {
+        CubeFace::Right
+    } else if coord == -point.x {
+        CubeFace::Left
+    } else if coord == point.y {
+        CubeFace::Up
+    } else if coord == -point.y {
+        CubeFace::Down
+    } else if coord == point.z {
+        CubeFace::Front
+    } else {
+        CubeFace::Back
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::floats::float_equal;
+    use crate::tuples::{Point, Tuple};
+    use crate::uv::{cube_map, cylindrical_map, planar_map, spherical_map, CubeFace};
+    use std::f64::consts::FRAC_1_SQRT_2;
+
+    fn assert_uv(actual: (f64, f64), expected: (f64, f64)) {
+        assert!(
+            float_equal(actual.0, expected.0) && float_equal(actual.1, expected.1),
+            "expected {:?}, got {:?}",
+            expected,
+            actual
+        );
+    }
+
+    #[test]
+    fn using_a_spherical_mapping_on_a_3d_point() {
+        let cases = [
+            (Point::new(0.0, 0.0, -1.0), (0.0, 0.5)),
+            (Point::new(1.0, 0.0, 0.0), (0.25, 0.5)),
+            (Point::new(0.0, 0.0, 1.0), (0.5, 0.5)),
+            (Point::new(-1.0, 0.0, 0.0), (0.75, 0.5)),
+            (Point::new(0.0, 1.0, 0.0), (0.5, 1.0)),
+            (Point::new(0.0, -1.0, 0.0), (0.5, 0.0)),
+            (Point::new(FRAC_1_SQRT_2, FRAC_1_SQRT_2, 0.0), (0.25, 0.75)),
+        ];
+        for (point, expected) in cases {
+            assert_uv(spherical_map(point), expected);
+        }
+    }
+
+    #[test]
+    fn using_a_planar_mapping_on_a_3d_point() {
+        let cases = [
+            (Point::new(0.25, 0.0, 0.5), (0.25, 0.5)),
+            (Point::new(0.25, 0.0, -0.25), (0.25, 0.75)),
+            (Point::new(0.25, 0.5, -0.25), (0.25, 0.75)),
+            (Point::new(1.25, 0.0, 0.5), (0.25, 0.5)),
+            (Point::new(0.25, 0.0, -1.75), (0.25, 0.25)),
+            (Point::new(1.0, 0.0, -1.0), (0.0, 0.0)),
+            (Point::new(0.0, 0.0, 0.0), (0.0, 0.0)),
+        ];
+        for (point, expected) in cases {
+            assert_uv(planar_map(point), expected);
+        }
+    }
+
+    #[test]
+    fn using_a_cylindrical_mapping_on_a_3d_point() {
+        let cases = [
+            (Point::new(0.0, 0.0, -1.0), (0.0, 0.0)),
+            (Point::new(0.0, 0.5, -1.0), (0.0, 0.5)),
+            (Point::new(0.0, 1.0, -1.0), (0.0, 0.0)),
+            (Point::new(FRAC_1_SQRT_2, 0.5, -FRAC_1_SQRT_2), (0.125, 0.5)),
+            (Point::new(1.0, 0.5, 0.0), (0.25, 0.5)),
+            (Point::new(FRAC_1_SQRT_2, 0.5, FRAC_1_SQRT_2), (0.375, 0.5)),
+            (Point::new(0.0, -0.25, 1.0), (0.5, 0.75)),
+            (Point::new(-FRAC_1_SQRT_2, 0.5, FRAC_1_SQRT_2), (0.625, 0.5)),
+            (Point::new(-1.0, 1.25, 0.0), (0.75, 0.25)),
+            (
+                Point::new(-FRAC_1_SQRT_2, 0.5, -FRAC_1_SQRT_2),
+                (0.875, 0.5),
+            ),
+        ];
+        for (point, expected) in cases {
+            assert_uv(cylindrical_map(point), expected);
+        }
+    }
+
+    #[test]
+    fn identifying_the_face_of_a_cube_from_a_point() {
+        let cases = [
+            (Point::new(-1.0, 0.5, -0.25), CubeFace::Left),
+            (Point::new(1.1, -0.75, 0.8), CubeFace::Right),
+            (Point::new(0.1, 0.6, 0.9), CubeFace::Front),
+            (Point::new(-0.7, 0.0, -2.0), CubeFace::Back),
+            (Point::new(0.5, 1.0, 0.9), CubeFace::Up),
+            (Point::new(-0.2, -1.3, 1.1), CubeFace::Down),
+        ];
+        for (point, expected) in cases {
+            assert_eq!(cube_map(point).0, expected);
+        }
+    }
+
+    #[test]
+    fn uv_mapping_the_faces_of_a_cube() {
+        let cases = [
+            (Point::new(-0.5, 0.5, 1.0), (0.25, 0.75)),
+            (Point::new(0.5, -0.5, 1.0), (0.75, 0.25)),
+            (Point::new(0.5, 0.5, -1.0), (0.25, 0.75)),
+            (Point::new(-1.0, 0.5, -0.5), (0.25, 0.75)),
+            (Point::new(1.0, 0.5, 0.5), (0.25, 0.75)),
+            (Point::new(-0.5, 1.0, -0.5), (0.25, 0.75)),
+            (Point::new(-0.5, -1.0, 0.5), (0.25, 0.75)),
+        ];
+        for (point, expected) in cases {
+            let (_, u, v) = cube_map(point);
+            assert_uv((u, v), expected);
+        }
+    }
+}
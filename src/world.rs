@@ -1,73 +1,548 @@
+use crate::canvas::Canvas;
 use crate::color::Color;
+use crate::floats::EPSILON;
 use crate::intersections::{Computations, Intersection};
-use crate::lights::PointLight;
+use crate::lights::{Light, PointLight};
 use crate::materials::Material;
 use crate::matrices::Matrix4;
 use crate::objects::{Intersectable, Object};
 use crate::rays::Ray;
+use crate::rng::Rng;
 use crate::sphere::Sphere;
-use crate::tuples::{Point, Tuple};
+use crate::stats::{RenderCounters, RenderStats};
+use crate::tuples::{Point, Tuple, Vector};
+use crate::uv::spherical_map;
+use std::f64::consts::PI;
+use std::fmt;
+use std::sync::Arc;
+
+// What a ray sees when it misses every object
+#[derive(Debug, Clone, PartialEq)]
+pub enum Background {
+    Solid(Color),
+    // Blends from bottom (looking straight down) to top (looking straight up)
+    Gradient { top: Color, bottom: Color },
+    // An equirectangular image wrapped around the whole scene, e.g. a panorama loaded with
+    // Canvas::from_ppm_file. Shared so several worlds can use one image without copying it.
+    EnvironmentMap(Arc<Canvas>),
+}
+
+impl Background {
+    pub fn color_at(&self, ray: Ray) -> Color {
+        match self {
+            Background::Solid(color) => *color,
+            Background::Gradient { top, bottom } => {
+                let t = 0.5 * (ray.direction.normalize().y + 1.0);
+                bottom.lerp(top, t)
+            }
+            // An empty image has no pixels to sample
+            Background::EnvironmentMap(image) if image.width == 0 || image.height == 0 => {
+                Color::black()
+            }
+            Background::EnvironmentMap(image) => {
+                let d = ray.direction;
+                let (u, v) = spherical_map(Point::new(d.x, d.y, d.z));
+                let x = (u * (image.width - 1) as f64).round() as u32;
+                let y = ((1.0 - v) * (image.height - 1) as f64).round() as u32;
+                image.pixel_at(x, y)
+            }
+        }
+    }
+}
+
+impl Default for Background {
+    fn default() -> Self {
+        Background::Solid(Color::black())
+    }
+}
+
+// Exponential distance fog that fades surfaces towards color the further away they are
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Fog {
+    pub color: Color,
+    pub density: f64,
+}
+
+impl Fog {
+    // How much of a surface at distance is hidden, from 0.0 to 1.0
+    pub fn amount(&self, distance: f64) -> f64 {
+        1.0 - (-self.density * distance).exp()
+    }
+
+    pub fn apply(&self, color: Color, distance: f64) -> Color {
+        color.lerp(&self.color, self.amount(distance))
+    }
+}
+
+// Darkens the ambient term in creases by casting samples rays over the hemisphere around
+// each hit and counting how many hit something closer than radius
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct AoConfig {
+    pub samples: u32,
+    pub radius: f64,
+}
+
+// Everything known about where a ray hit, for tools that need more than the final color
+#[derive(Debug, Clone, Copy)]
+pub struct HitRecord<'a> {
+    pub t: f64,
+    pub point: Point,
+    // Faces back towards the ray, even when the hit is on the inside of an object
+    pub normal: Vector,
+    pub object: &'a Object,
+    // Position of object in World::objects
+    pub index: usize,
+}
+
+// One step in working out a ray's color, recorded by World::color_at_traced
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum TraceEvent {
+    // The ray hit nothing, and saw this
+    Miss {
+        color: Color,
+    },
+    Hit {
+        index: usize,
+        t: f64,
+        point: Point,
+        normal: Vector,
+    },
+    // Light the hit's material gives off itself. Only recorded when it isn't black.
+    Emission {
+        color: Color,
+    },
+    // How much ambient light reached the hit, from 0.0 (fully occluded) to 1.0. Only
+    // recorded when ambient occlusion is on.
+    AmbientOcclusion {
+        visibility: f64,
+    },
+    // How much of lights[index] reached the hit, from 0.0 (in shadow) to 1.0
+    Light {
+        index: usize,
+        intensity: f64,
+    },
+    // How much of the hit's color was replaced by fog, from 0.0 to 1.0
+    Fog {
+        amount: f64,
+    },
+}
+
+// Likely mistakes that render without errors but probably not as intended
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum SceneWarning {
+    // Only emission will show up
+    NoLights,
+    // Index into lights of a light with no intensity
+    DarkLight(usize),
+    // Index into objects of an object whose transform can't be inverted, so it can't be seen
+    DegenerateObject(usize),
+    // Index into objects of an object whose material gives off no light and reflects none
+    InvisibleMaterial(usize),
+    // The camera's transform can't be inverted, e.g. a view from a point to itself, so
+    // it has no rays to cast
+    DegenerateCamera,
+    // A perspective camera's field of view, which can only show less than half a turn
+    WideFieldOfView(f64),
+}
+
+impl fmt::Display for SceneWarning {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SceneWarning::NoLights => write!(f, "The scene has no lights"),
+            SceneWarning::DarkLight(i) => write!(f, "Light {} has an intensity of zero", i),
+            SceneWarning::DegenerateObject(i) => {
+                write!(f, "Object {} has a transform that can't be inverted", i)
+            }
+            SceneWarning::InvisibleMaterial(i) => {
+                write!(f, "Object {} has a material that will always be black", i)
+            }
+            SceneWarning::DegenerateCamera => {
+                write!(f, "The camera has a transform that can't be inverted")
+            }
+            SceneWarning::WideFieldOfView(fov) => {
+                write!(
+                    f,
+                    "The camera's field of view of {} is not less than pi",
+                    fov
+                )
+            }
+        }
+    }
+}
+
+// Where color_at_traced collects events. Plain color_at passes None, so it doesn't pay for
+// building them.
+type Events<'a> = Option<&'a mut Vec<TraceEvent>>;
+
+fn record(events: &mut Events, event: impl FnOnce() -> TraceEvent) {
+    if let Some(events) = events {
+        events.push(event());
+    }
+}
 
 pub struct World {
     pub objects: Vec<Object>,
-    pub light_source: Option<PointLight>,
+    pub lights: Vec<Light>,
+    pub background: Background,
+    // Off by default, since it multiplies the number of rays per hit
+    pub ambient_occlusion: Option<AoConfig>,
+    pub fog: Option<Fog>,
+    // How far hits are pushed off surfaces before casting shadow rays. Scenes far from the
+    // origin lose float precision and may need more than the default to avoid shadow acne.
+    pub shadow_bias: f64,
+    pub counters: RenderCounters,
+    // Whether rays skip objects whose world bounds they miss. Turned on by
+    // build_culling_bounds.
+    pub culling: bool,
 }
 
 impl World {
     pub fn new() -> World {
         World {
             objects: Vec::new(),
-            light_source: None,
+            lights: Vec::new(),
+            background: Background::default(),
+            ambient_occlusion: None,
+            fog: None,
+            shadow_bias: EPSILON,
+            counters: RenderCounters::default(),
+            culling: false,
+        }
+    }
+
+    // Returns the new object's index. Removing an earlier object shifts the indices after it.
+    pub fn add_object(&mut self, mut object: Object) -> usize {
+        object.save_world_bounds();
+        self.objects.push(object);
+        self.objects.len() - 1
+    }
+
+    pub fn remove_object(&mut self, index: usize) -> Option<Object> {
+        if index < self.objects.len() {
+            Some(self.objects.remove(index))
+        } else {
+            None
+        }
+    }
+
+    pub fn get_object(&self, index: usize) -> Option<&Object> {
+        self.objects.get(index)
+    }
+
+    pub fn get_object_mut(&mut self, index: usize) -> Option<&mut Object> {
+        self.objects.get_mut(index)
+    }
+
+    // Checks for mistakes that would otherwise only show up as a black or empty render
+    pub fn validate(&self) -> Vec<SceneWarning> {
+        let mut warnings = Vec::new();
+        if self.lights.is_empty() {
+            warnings.push(SceneWarning::NoLights);
+        }
+        for (index, light) in self.lights.iter().enumerate() {
+            if light.intensity() == Color::black() {
+                warnings.push(SceneWarning::DarkLight(index));
+            }
+        }
+        for (index, object) in self.objects.iter().enumerate() {
+            if object.is_degenerate() {
+                warnings.push(SceneWarning::DegenerateObject(index));
+            }
+            let material = object.material();
+            let reflects_light = material.ambient + material.diffuse + material.specular > 0.0;
+            if material.emission == Color::black()
+                && (material.color == Color::black() || !reflects_light)
+            {
+                warnings.push(SceneWarning::InvisibleMaterial(index));
+            }
+        }
+        warnings
+    }
+
+    // Adds a wide, matte floor at height y for quick test scenes, returning its index. It's a
+    // solid color, since there are no patterns to checker it with yet.
+    pub fn add_floor(&mut self, y: f64) -> usize {
+        let extent = 1000.0;
+        let floor = Object::quad_builder(-extent, extent, -extent, extent)
+            .transform(Matrix4::translate(0.0, y, 0.0))
+            .material(Material {
+                color: Color::new(0.9, 0.9, 0.9),
+                specular: 0.0,
+                ..Default::default()
+            })
+            .build();
+        self.add_object(floor)
+    }
+
+    // Saves every object's world bounds and turns culling on. The bounds stay with each
+    // object, and are saved again whenever its transform is changed through the object.
+    pub fn build_culling_bounds(&mut self) {
+        for object in self.objects.iter_mut() {
+            object.save_world_bounds();
         }
+        self.culling = true;
     }
 
-    pub fn intersect(&self, ray: Ray) -> Vec<Intersection> {
+    pub fn intersect(&self, ray: Ray) -> Vec<Intersection<'_>> {
         let mut intersections: Vec<Intersection> = Vec::new();
+        let mut tests = 0;
         for object in self.objects.iter() {
-            let object_intersections = object.intersect(ray);
-            for intersection in object_intersections {
-                intersections.push(Intersection {
-                    object,
-                    t: intersection,
-                });
+            // Objects whose transform was replaced without going through the object have no
+            // saved bounds, so they're always tested
+            let culled = self.culling
+                && object
+                    .transform()
+                    .saved_world_bounds()
+                    .is_some_and(|bounds| !bounds.intersects(ray));
+            if culled {
+                continue;
             }
+            tests += 1;
+            intersections.extend(object.intersect_with_object(ray));
         }
+        self.counters.count_intersection_tests(tests);
         intersections.sort_by(|a, b| a.t.partial_cmp(&b.t).unwrap());
         intersections
     }
 
+    // Work counted since the world was created or the stats were last reset
+    pub fn stats(&self) -> RenderStats {
+        self.counters.snapshot()
+    }
+
+    pub fn reset_stats(&self) {
+        self.counters.reset();
+    }
+
+    // Index into objects of an object borrowed from this world
+    fn index_of(&self, object: &Object) -> Option<usize> {
+        self.objects.iter().position(|o| std::ptr::eq(o, object))
+    }
+
+    // The closest hit in front of the ray's origin, if any
+    pub fn cast(&self, ray: Ray) -> Option<HitRecord<'_>> {
+        let hit = Intersection::hit(self.intersect(ray))?;
+        let index = self.index_of(hit.object)?;
+        let comps = hit.prepare_computations(ray);
+        Some(HitRecord {
+            t: hit.t,
+            point: comps.point,
+            normal: comps.normal_vector,
+            object: hit.object,
+            index,
+        })
+    }
+
+    // Kept for scenes that only care about a single light
+    pub fn light_source(&self) -> Option<Light> {
+        self.lights.first().copied()
+    }
+
     pub fn shade_hit(&self, comps: Computations) -> Color {
-        let in_shadow = self.is_shadowed(comps.over_point);
-        comps.object.material().lighting(
-            self.light_source.unwrap(),
-            comps.point,
-            comps.eye_vector,
+        self.shade_hit_traced(comps, &mut None, None)
+    }
+
+    // rng is where jittered area lights get their samples. Without one they're seeded from
+    // the hit point instead.
+    fn shade_hit_traced(
+        &self,
+        comps: Computations,
+        events: &mut Events,
+        mut rng: Option<&mut Rng>,
+    ) -> Color {
+        let material = comps.object.material();
+        // Emission is added once here rather than in lighting, so it doesn't scale with the
+        // number of lights and still shows up when there are none
+        let emission = material.emission;
+        if emission != Color::black() {
+            record(events, || TraceEvent::Emission { color: emission });
+        }
+        let visibility = self.ambient_visibility_at_time(
+            comps.over_point,
             comps.normal_vector,
-            in_shadow,
-        )
+            comps.time_of_ray,
+        );
+        if self.ambient_occlusion.is_some() {
+            record(events, || TraceEvent::AmbientOcclusion { visibility });
+        }
+        let occlusion = 1.0 - visibility;
+        let mut color = emission;
+        for (index, light) in self.lights.iter().enumerate() {
+            // Drawn once, so the shadow rays and the shading see the light from the same spots
+            let samples = match rng.as_deref_mut() {
+                Some(rng) => light.sample_points_from(rng),
+                None => light.sample_points(comps.point),
+            };
+            let time = comps.time_of_ray;
+            let mut intensity =
+                self.intensity_with_samples(light, comps.over_point, &samples, time);
+            if material.two_sided {
+                // Lights behind the surface are checked from just behind it too, so the
+                // surface doesn't shadow itself
+                let under_point = comps.point - comps.normal_vector * self.shadow_bias;
+                intensity =
+                    intensity.max(self.intensity_with_samples(light, under_point, &samples, time));
+            }
+            record(events, || TraceEvent::Light { index, intensity });
+            let lit = material.lighting_with_samples(
+                *light,
+                comps.point,
+                comps.eye_vector,
+                comps.normal_vector,
+                intensity,
+                &samples,
+            );
+            color = color + lit - material.ambient_color(light) * occlusion;
+        }
+        color
+    }
+
+    // Fraction of the hemisphere around normal that is open, from 0.0 (fully enclosed) to 1.0.
+    // Always 1.0 when ambient occlusion is off.
+    pub fn ambient_visibility(&self, point: Point, normal: Vector) -> f64 {
+        self.ambient_visibility_at_time(point, normal, 0.0)
+    }
+
+    // Same as ambient_visibility, but with moving objects where they are at time
+    pub fn ambient_visibility_at_time(&self, point: Point, normal: Vector, time: f64) -> f64 {
+        let Some(config) = self.ambient_occlusion else {
+            return 1.0;
+        };
+        if config.samples == 0 {
+            return 1.0;
+        }
+
+        let (tangent, bitangent, normal) = normal.orthonormal_basis();
+
+        // Cosine-weighted directions laid out on a golden-angle spiral, so renders are
+        // reproducible and there's no noise from random sampling
+        let golden_angle = PI * (3.0 - 5.0_f64.sqrt());
+        let open = (0..config.samples)
+            .filter(|&i| {
+                let radius = ((i as f64 + 0.5) / config.samples as f64).sqrt();
+                let theta = i as f64 * golden_angle;
+                let direction = tangent * (radius * theta.cos())
+                    + bitangent * (radius * theta.sin())
+                    + normal * (1.0 - radius * radius).sqrt();
+                !self.is_occluded(Ray::at_time(point, direction, time), config.radius)
+            })
+            .count();
+        open as f64 / config.samples as f64
     }
 
     pub fn color_at(&self, ray: Ray) -> Color {
+        self.color_at_recording(ray, &mut None, None).0
+    }
+
+    // Same as color_at, but also says whether the ray hit an object rather than the
+    // background. Jittered area lights take their samples from rng when there is one, e.g.
+    // the camera's seeded generator for the pixel.
+    pub fn color_at_with_hit(&self, ray: Ray, rng: Option<&mut Rng>) -> (Color, bool) {
+        self.color_at_recording(ray, &mut None, rng)
+    }
+
+    fn color_at_recording(
+        &self,
+        ray: Ray,
+        events: &mut Events,
+        rng: Option<&mut Rng>,
+    ) -> (Color, bool) {
+        self.counters.count_ray();
         let intersections = self.intersect(ray);
         let hit = Intersection::hit(intersections);
         match hit {
             Some(hit) => {
-                let comps = hit.prepare_computations(ray);
-                self.shade_hit(comps)
+                let distance = hit.t * ray.direction.magnitude();
+                let comps = hit.prepare_computations_with_bias(ray, self.shadow_bias);
+                record(events, || TraceEvent::Hit {
+                    index: self.index_of(hit.object).unwrap_or_default(),
+                    t: hit.t,
+                    point: comps.point,
+                    normal: comps.normal_vector,
+                });
+                let color = self.shade_hit_traced(comps, events, rng);
+                let color = match self.fog {
+                    Some(fog) => {
+                        record(events, || TraceEvent::Fog {
+                            amount: fog.amount(distance),
+                        });
+                        fog.apply(color, distance)
+                    }
+                    None => color,
+                };
+                (color, true)
+            }
+            // Misses are infinitely far away, so they disappear into the fog completely
+            None => {
+                let color = self
+                    .fog
+                    .map_or(self.background.color_at(ray), |fog| fog.color);
+                record(events, || TraceEvent::Miss { color });
+                (color, false)
             }
-            None => Color::black(),
         }
     }
 
-    pub fn is_shadowed(&self, point: Point) -> bool {
-        // Measure the distance from point to the light source
-        let v = self.light_source.unwrap().position - point;
-        let distance = v.magnitude();
-        let direction = v.normalize();
+    // Same as color_at, but also returns what happened along the way, for debugging a single
+    // pixel. The events are recorded by the same code that works out the color.
+    pub fn color_at_traced(&self, ray: Ray) -> (Color, Vec<TraceEvent>) {
+        let mut events = Vec::new();
+        let (color, _) = self.color_at_recording(ray, &mut Some(&mut events), None);
+        (color, events)
+    }
 
-        // Create a ray from point toward the light source, then intersect the world
-        let ray = Ray::new(point, direction);
-        let intersections = self.intersect(ray);
+    // Fraction of the light that reaches point: the share of its samples that are visible,
+    // scaled down for points outside a spotlight's cone
+    pub fn intensity_at(&self, light: &Light, point: Point) -> f64 {
+        self.intensity_at_time(light, point, 0.0)
+    }
+
+    // Same as intensity_at, but with moving objects where they are at time
+    pub fn intensity_at_time(&self, light: &Light, point: Point, time: f64) -> f64 {
+        self.intensity_with_samples(light, point, &light.sample_points(point), time)
+    }
+
+    // Same as intensity_at_time, but only checks the given points on the light
+    fn intensity_with_samples(
+        &self,
+        light: &Light,
+        point: Point,
+        samples: &[Point],
+        time: f64,
+    ) -> f64 {
+        let falloff = light.falloff(point);
+        if falloff == 0.0 {
+            return 0.0;
+        }
+
+        let samples = light.directions_to(point, samples);
+        if samples.is_empty() {
+            return 0.0;
+        }
+        let visible = samples
+            .iter()
+            .filter(|(direction, distance)| {
+                !self.is_occluded(Ray::at_time(point, *direction, time), *distance)
+            })
+            .count();
+        falloff * visible as f64 / samples.len() as f64
+    }
+
+    // A point light at light_position either reaches point fully or not at all, so this is
+    // intensity_at with the answer rounded to a bool
+    pub fn is_shadowed(&self, light_position: Point, point: Point) -> bool {
+        let light = Light::from(PointLight::new(light_position, Color::white()));
+        self.intensity_at(&light, point) == 0.0
+    }
+
+    // Whether anything that casts a shadow is hit along ray before distance
+    fn is_occluded(&self, ray: Ray, distance: f64) -> bool {
+        self.counters.count_shadow_ray();
+        let intersections = self
+            .intersect(ray)
+            .into_iter()
+            .filter(|i| i.object.material().casts_shadow)
+            .collect();
 
         // See if there was a hit and if so, whether t is less than distance.
         let hit = Intersection::hit(intersections);
@@ -90,48 +565,273 @@ impl Default for World {
             material,
             ..Default::default()
         };
-        let sphere2 = Sphere {
-            transformation: Matrix4::scale(0.5, 0.5, 0.5),
-            ..Default::default()
-        };
+        let sphere2 = Sphere::with_transform(Matrix4::scale(0.5, 0.5, 0.5));
 
         let objects: Vec<Object> = Vec::from([Object::Sphere(sphere1), Object::Sphere(sphere2)]);
 
         World {
             objects,
-            light_source: Some(PointLight::new(
+            lights: vec![Light::from(PointLight::new(
                 Point::new(-10.0, 10.0, -10.0),
                 Color::new(1.0, 1.0, 1.0),
-            )),
+            ))],
+            background: Background::default(),
+            ambient_occlusion: None,
+            fog: None,
+            shadow_bias: EPSILON,
+            counters: RenderCounters::default(),
+            culling: false,
         }
     }
 }
 
 #[cfg(test)]
 mod tests {
+    use crate::canvas::Canvas;
     use crate::color::Color;
+    use crate::floats::EPSILON;
     use crate::intersections::Intersection;
-    use crate::lights::PointLight;
+    use crate::lights::{AreaLight, DirectionalLight, Light, PointLight, SpotLight};
     use crate::materials::Material;
     use crate::matrices::Matrix4;
     use crate::objects::{Intersectable, Object};
     use crate::rays::Ray;
+    use crate::rng::Rng;
     use crate::sphere::Sphere;
+    use crate::transformations::Transform;
     use crate::tuples::{Point, Tuple, Vector};
-    use crate::world::World;
+    use crate::world::{AoConfig, Background, Fog, SceneWarning, TraceEvent, World};
+    use std::f64::consts::PI;
+    use std::sync::Arc;
 
     #[test]
     fn empty_world() {
         let world = World::new();
         assert_eq!(world.objects.len(), 0);
-        assert!(world.light_source.is_none());
+        assert!(world.lights.is_empty());
     }
 
     #[test]
     fn default_world() {
         let world = World::default();
         assert_eq!(world.objects.len(), 2);
-        assert!(world.light_source.is_some());
+        assert_eq!(world.lights.len(), 1);
+        assert_eq!(world.shadow_bias, EPSILON);
+    }
+
+    #[test]
+    fn casting_a_ray_returns_the_hit_record() {
+        let world = World::default();
+        let ray = Ray::new(Point::new(0.0, 0.0, -5.0), Vector::new(0.0, 0.0, 1.0));
+        let hit = world.cast(ray).unwrap();
+        assert_eq!(hit.t, 4.0);
+        assert_eq!(hit.point, Point::new(0.0, 0.0, -1.0));
+        assert_eq!(hit.normal, Vector::new(0.0, 0.0, -1.0));
+        assert_eq!(hit.index, 0);
+        assert_eq!(*hit.object, world.objects[0]);
+    }
+
+    #[test]
+    fn casting_a_ray_from_inside_finds_the_inner_sphere() {
+        let world = World::default();
+        let ray = Ray::new(Point::new(0.0, 0.0, 0.0), Vector::new(0.0, 1.0, 0.0));
+        let hit = world.cast(ray).unwrap();
+        assert_eq!(hit.index, 1);
+        assert_eq!(hit.point, Point::new(0.0, 0.5, 0.0));
+        assert_eq!(hit.normal, Vector::new(0.0, -1.0, 0.0));
+
+        let miss = Ray::new(Point::new(0.0, 5.0, 0.0), Vector::new(0.0, 1.0, 0.0));
+        assert!(world.cast(miss).is_none());
+    }
+
+    #[test]
+    fn tracing_a_ray_records_the_hit_and_lights() {
+        let world = World::default();
+        let ray = Ray::new(Point::new(0.0, 0.0, -5.0), Vector::new(0.0, 0.0, 1.0));
+        let (color, events) = world.color_at_traced(ray);
+        assert_eq!(color, world.color_at(ray));
+        assert_eq!(
+            events,
+            vec![
+                TraceEvent::Hit {
+                    index: 0,
+                    t: 4.0,
+                    point: Point::new(0.0, 0.0, -1.0),
+                    normal: Vector::new(0.0, 0.0, -1.0),
+                },
+                TraceEvent::Light {
+                    index: 0,
+                    intensity: 1.0,
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn tracing_a_ray_records_misses_and_fog() {
+        let world = World {
+            fog: Some(Fog {
+                color: Color::white(),
+                density: 0.5,
+            }),
+            ..Default::default()
+        };
+        let miss = Ray::new(Point::new(0.0, 5.0, -5.0), Vector::new(0.0, 0.0, 1.0));
+        assert_eq!(
+            world.color_at_traced(miss).1,
+            vec![TraceEvent::Miss {
+                color: Color::white()
+            }]
+        );
+
+        let hit = Ray::new(Point::new(0.0, 0.0, -5.0), Vector::new(0.0, 0.0, 1.0));
+        let (_, events) = world.color_at_traced(hit);
+        let amount = 1.0 - (-2.0_f64).exp();
+        assert_eq!(events.last(), Some(&TraceEvent::Fog { amount }));
+    }
+
+    #[test]
+    fn tracing_a_ray_counts_the_same_work_as_color_at() {
+        let world = World::default();
+        let ray = Ray::new(Point::new(0.0, 0.0, -5.0), Vector::new(0.0, 0.0, 1.0));
+        world.color_at(ray);
+        let plain = world.stats();
+        world.reset_stats();
+        world.color_at_traced(ray);
+        assert_eq!(world.stats(), plain);
+    }
+
+    #[test]
+    fn tracing_a_ray_records_the_intensity_used_for_shading() {
+        let light = Light::from(PointLight::new(Point::new(0.0, -10.0, 0.0), Color::white()));
+        let mut disk = Object::disk(0.0, 2.0);
+        disk.set_material(Material {
+            two_sided: true,
+            ..Default::default()
+        });
+        let world = World {
+            objects: vec![disk],
+            lights: vec![light],
+            ..Default::default()
+        };
+        let ray = Ray::new(Point::new(0.0, 5.0, 0.0), Vector::new(0.0, -1.0, 0.0));
+        let (color, events) = world.color_at_traced(ray);
+        assert_eq!(color, Color::new(1.0, 1.0, 1.0));
+        assert!(events.contains(&TraceEvent::Light {
+            index: 0,
+            intensity: 1.0,
+        }));
+    }
+
+    #[test]
+    fn tracing_a_ray_records_emission_and_ambient_occlusion() {
+        let glow = Color::new(1.0, 0.8, 0.2);
+        let mut sphere = Object::Sphere(Sphere::new());
+        sphere.set_material(Material {
+            emission: glow,
+            ..Default::default()
+        });
+        let world = World {
+            objects: vec![sphere],
+            ambient_occlusion: Some(AoConfig {
+                samples: 4,
+                radius: 1.0,
+            }),
+            ..Default::default()
+        };
+        let ray = Ray::new(Point::new(0.0, 0.0, -5.0), Vector::new(0.0, 0.0, 1.0));
+        let (_, events) = world.color_at_traced(ray);
+        assert!(events.contains(&TraceEvent::Emission { color: glow }));
+        assert!(events.contains(&TraceEvent::AmbientOcclusion { visibility: 1.0 }));
+    }
+
+    #[test]
+    fn the_default_world_is_valid() {
+        assert!(World::default().validate().is_empty());
+    }
+
+    #[test]
+    fn validating_a_world_with_mistakes() {
+        let mut world = World::new();
+        assert_eq!(world.validate(), vec![SceneWarning::NoLights]);
+
+        world.lights.push(Light::from(PointLight::new(
+            Point::new(0.0, 10.0, 0.0),
+            Color::black(),
+        )));
+        let mut flattened = Object::Sphere(Sphere::new());
+        flattened.set_transform(Matrix4::scale(1.0, 0.0, 1.0));
+        world.add_object(flattened);
+        let mut black = Object::Sphere(Sphere::new());
+        black.set_material(Material {
+            color: Color::black(),
+            ..Default::default()
+        });
+        world.add_object(black);
+
+        assert_eq!(
+            world.validate(),
+            vec![
+                SceneWarning::DarkLight(0),
+                SceneWarning::DegenerateObject(0),
+                SceneWarning::InvisibleMaterial(1),
+            ]
+        );
+        assert_eq!(
+            SceneWarning::DarkLight(0).to_string(),
+            "Light 0 has an intensity of zero"
+        );
+    }
+
+    #[test]
+    fn adding_a_floor() {
+        let mut world = World::new();
+        let index = world.add_floor(-1.0);
+        let ray = Ray::new(Point::new(50.0, 5.0, -30.0), Vector::new(0.0, -1.0, 0.0));
+        let hit = world.cast(ray).unwrap();
+        assert_eq!(hit.index, index);
+        assert_eq!(hit.point, Point::new(50.0, -1.0, -30.0));
+        assert_eq!(hit.normal, Vector::new(0.0, 1.0, 0.0));
+    }
+
+    #[test]
+    fn culling_skips_objects_the_ray_cannot_hit() {
+        let mut world = World::new();
+        for i in 0..10 {
+            let sphere = Sphere::with_transform(Matrix4::translate(i as f64 * 3.0, 0.0, 0.0));
+            world.add_object(Object::Sphere(sphere));
+        }
+        let ray = Ray::new(Point::new(6.0, 0.0, -5.0), Vector::new(0.0, 0.0, 1.0));
+        let without_culling: Vec<f64> = world.intersect(ray).iter().map(|i| i.t).collect();
+        assert_eq!(world.stats().intersection_tests, 10);
+
+        world.build_culling_bounds();
+        world.reset_stats();
+        let with_culling: Vec<f64> = world.intersect(ray).iter().map(|i| i.t).collect();
+        assert_eq!(with_culling, without_culling);
+        assert_eq!(world.stats().intersection_tests, 1);
+    }
+
+    #[test]
+    fn culling_still_hits_objects_moved_in_place() {
+        let mut world = World::default();
+        world.build_culling_bounds();
+        let ray = Ray::new(Point::new(5.0, 0.0, -5.0), Vector::new(0.0, 0.0, 1.0));
+        assert!(world.intersect(ray).is_empty());
+
+        world.objects[1].set_transform(Matrix4::translate(5.0, 0.0, 0.0));
+        assert_eq!(world.cast(ray).unwrap().index, 1);
+
+        world.objects[1].set_transform(Matrix4::identity());
+        world.objects[1].set_motion(Matrix4::translate(5.0, 0.0, 0.0));
+        let moving = Ray::at_time(Point::new(5.0, 0.0, -5.0), Vector::new(0.0, 0.0, 1.0), 1.0);
+        assert_eq!(world.cast(moving).unwrap().index, 1);
+
+        // Replacing the transform wholesale drops the saved bounds, so the object isn't culled
+        if let Object::Sphere(sphere) = &mut world.objects[0] {
+            sphere.transform = Transform::new(Matrix4::translate(5.0, 0.0, 0.0));
+        }
+        assert_eq!(world.cast(ray).unwrap().index, 0);
     }
 
     #[test]
@@ -151,10 +851,7 @@ mod tests {
         let world = World::default();
         let ray = Ray::new(Point::new(0.0, 0.0, -5.0), Vector::new(0.0, 0.0, 1.0));
         let shape = &world.objects[0];
-        let intersection = Intersection {
-            object: shape,
-            t: shape.intersect(ray)[0],
-        };
+        let intersection = Intersection::new(shape.intersect(ray)[0], shape);
         let computations = intersection.prepare_computations(ray);
         let color = world.shade_hit(computations);
         assert_eq!(color, Color::new(0.38066, 0.47583, 0.2855));
@@ -163,10 +860,10 @@ mod tests {
     #[test]
     fn shading_intersection_from_inside() {
         let world = World {
-            light_source: Some(PointLight::new(
+            lights: vec![Light::from(PointLight::new(
                 Point::new(0.0, 0.25, 0.0),
                 Color::new(1.0, 1.0, 1.0),
-            )),
+            ))],
             ..Default::default()
         };
         let ray = Ray::new(Point::new(0.0, 0.0, 0.0), Vector::new(0.0, 0.0, 1.0));
@@ -221,29 +918,33 @@ mod tests {
     #[test]
     fn there_is_no_shadow_when_nothing_is_collinear_with_point_and_light() {
         let world = World::default();
+        let light_position = world.light_source().unwrap().position().unwrap();
         let point = Point::new(0.0, 10.0, 0.0);
-        assert!(!world.is_shadowed(point));
+        assert!(!world.is_shadowed(light_position, point));
     }
 
     #[test]
     fn shadow_when_an_object_is_between_point_and_light() {
         let world = World::default();
+        let light_position = world.light_source().unwrap().position().unwrap();
         let point = Point::new(10.0, -10.0, 10.0);
-        assert!(world.is_shadowed(point));
+        assert!(world.is_shadowed(light_position, point));
     }
 
     #[test]
     fn there_is_no_shadow_when_an_object_is_behind_the_light() {
         let world = World::default();
+        let light_position = world.light_source().unwrap().position().unwrap();
         let point = Point::new(-20.0, 20.0, -20.0);
-        assert!(!world.is_shadowed(point));
+        assert!(!world.is_shadowed(light_position, point));
     }
 
     #[test]
     fn there_is_no_shadow_when_an_object_is_behind_the_point() {
         let world = World::default();
+        let light_position = world.light_source().unwrap().position().unwrap();
         let point = Point::new(-2.0, 2.0, -2.0);
-        assert!(!world.is_shadowed(point));
+        assert!(!world.is_shadowed(light_position, point));
     }
 
     #[test]
@@ -253,8 +954,12 @@ mod tests {
         s2.set_transform(Matrix4::translate(0.0, 0.0, 10.0));
 
         let world = World {
-            light_source: Some(PointLight::new(Point::new(0.0, 0.0, -10.0), Color::white())),
+            lights: vec![Light::from(PointLight::new(
+                Point::new(0.0, 0.0, -10.0),
+                Color::white(),
+            ))],
             objects: vec![Object::Sphere(s1), Object::Sphere(s2)],
+            ..Default::default()
         };
 
         let ray = Ray::new(Point::new(0.0, 0.0, 5.0), Vector::new(0.0, 0.0, 1.0));
@@ -263,4 +968,436 @@ mod tests {
         let color = world.shade_hit(computations);
         assert_eq!(color, Color::new(0.1, 0.1, 0.1));
     }
+
+    #[test]
+    fn point_light_intensity_is_all_or_nothing() {
+        let world = World::default();
+        let cases = [
+            (Point::new(0.0, 1.0001, 0.0), 1.0),
+            (Point::new(-1.0001, 0.0, 0.0), 1.0),
+            (Point::new(0.0, 0.0, -1.0001), 1.0),
+            (Point::new(0.0, 0.0, 1.0001), 0.0),
+            (Point::new(1.0001, 0.0, 0.0), 0.0),
+            (Point::new(0.0, -1.0001, 0.0), 0.0),
+            (Point::new(0.0, 0.0, 0.0), 0.0),
+        ];
+        let light = world.light_source().unwrap();
+        for (point, expected) in cases {
+            assert_eq!(world.intensity_at(&light, point), expected);
+        }
+    }
+
+    #[test]
+    fn area_light_intensity_is_fractional() {
+        let corner = Point::new(-0.5, -0.5, -5.0);
+        let v1 = Vector::new(1.0, 0.0, 0.0);
+        let v2 = Vector::new(0.0, 1.0, 0.0);
+        let light = AreaLight::new(corner, v1, 2, v2, 2, Color::white());
+        let world = World {
+            lights: vec![Light::from(light)],
+            ..Default::default()
+        };
+        let cases = [
+            (Point::new(0.0, 0.0, 2.0), 0.0),
+            (Point::new(1.0, -1.0, 2.0), 0.25),
+            (Point::new(1.5, 0.0, 2.0), 0.5),
+            (Point::new(1.25, 1.25, 3.0), 0.75),
+            (Point::new(0.0, 0.0, -2.0), 1.0),
+        ];
+        for (point, expected) in cases {
+            assert_eq!(world.intensity_at(&world.lights[0], point), expected);
+        }
+    }
+
+    #[test]
+    fn jittered_area_lights_are_sampled_once_per_hit_from_the_rng() {
+        let mut light = AreaLight::new(
+            Point::new(-1.0, 5.0, -1.0),
+            Vector::new(2.0, 0.0, 0.0),
+            4,
+            Vector::new(0.0, 0.0, 2.0),
+            4,
+            Color::white(),
+        );
+        light.jitter = true;
+        let light = Light::from(light);
+        let mut world = World {
+            lights: vec![light],
+            ..World::new()
+        };
+        world.add_object(Object::Sphere(Sphere::new()));
+        let floor = world.add_floor(-1.0);
+
+        // A point on the floor in the sphere's soft shadow
+        let ray = Ray::new(Point::new(1.2, 5.0, 0.0), Vector::new(0.0, -1.0, 0.0));
+        let shade = |seed| world.color_at_with_hit(ray, Some(&mut Rng::new(seed))).0;
+        assert_eq!(shade(1), shade(1));
+        assert_ne!(shade(1), shade(2));
+
+        // The shadow rays and the shading use the same samples from the rng
+        let comps = Intersection::new(6.0, &world.objects[floor]).prepare_computations(ray);
+        let samples = light.sample_points_from(&mut Rng::new(1));
+        let intensity = world.intensity_with_samples(&light, comps.over_point, &samples, 0.0);
+        assert!(intensity > 0.0 && intensity < 1.0);
+        let expected = world.objects[floor].material().lighting_with_samples(
+            light,
+            comps.point,
+            comps.eye_vector,
+            comps.normal_vector,
+            intensity,
+            &samples,
+        );
+        assert_eq!(shade(1), expected);
+    }
+
+    #[test]
+    fn shade_hit_sums_the_contribution_of_every_light() {
+        let light = Light::from(PointLight::new(
+            Point::new(-10.0, 10.0, -10.0),
+            Color::new(1.0, 1.0, 1.0),
+        ));
+        let world = World {
+            lights: vec![light, light],
+            ..Default::default()
+        };
+        let ray = Ray::new(Point::new(0.0, 0.0, -5.0), Vector::new(0.0, 0.0, 1.0));
+        let shape = &world.objects[0];
+        let intersection = Intersection::new(4.0, shape);
+        let computations = intersection.prepare_computations(ray);
+        let color = world.shade_hit(computations);
+        assert_eq!(color, Color::new(0.38066, 0.47583, 0.2855) * 2.0);
+    }
+
+    #[test]
+    fn two_sided_surfaces_are_lit_from_behind() {
+        let light = Light::from(PointLight::new(Point::new(0.0, -10.0, 0.0), Color::white()));
+        let mut disk = Object::disk(0.0, 2.0);
+        let mut world = World {
+            objects: vec![disk],
+            lights: vec![light],
+            ..Default::default()
+        };
+        let ray = Ray::new(Point::new(0.0, 5.0, 0.0), Vector::new(0.0, -1.0, 0.0));
+        let one_sided = world.color_at(ray);
+        assert_eq!(one_sided, Color::new(0.1, 0.1, 0.1));
+
+        disk.set_material(Material {
+            two_sided: true,
+            ..Default::default()
+        });
+        world.objects = vec![disk];
+        assert_eq!(world.color_at(ray), Color::new(1.0, 1.0, 1.0));
+    }
+
+    #[test]
+    fn world_without_lights_is_dark() {
+        let world = World {
+            lights: Vec::new(),
+            ..Default::default()
+        };
+        let ray = Ray::new(Point::new(0.0, 0.0, -5.0), Vector::new(0.0, 0.0, 1.0));
+        assert_eq!(world.color_at(ray), Color::black());
+    }
+
+    #[test]
+    fn spotlight_only_lights_points_inside_its_cone() {
+        let light = Light::from(SpotLight::new(
+            Point::new(0.0, 0.0, -10.0),
+            Vector::new(0.0, 0.0, 1.0),
+            Color::white(),
+            PI / 36.0,
+            PI / 18.0,
+        ));
+        let world = World {
+            lights: vec![light],
+            ..Default::default()
+        };
+        assert_eq!(
+            world.intensity_at(&light, Point::new(0.0, 0.0, -1.0001)),
+            1.0
+        );
+        assert_eq!(world.intensity_at(&light, Point::new(0.0, 5.0, -1.0)), 0.0);
+    }
+
+    #[test]
+    fn ray_that_misses_sees_the_background() {
+        let sky = Color::new(0.6, 0.8, 1.0);
+        let world = World {
+            background: Background::Solid(sky),
+            ..Default::default()
+        };
+        let ray = Ray::new(Point::new(0.0, 0.0, -5.0), Vector::new(0.0, 1.0, 0.0));
+        assert_eq!(world.color_at(ray), sky);
+    }
+
+    #[test]
+    fn gradient_background_blends_by_ray_direction() {
+        let top = Color::new(0.5, 0.7, 1.0);
+        let bottom = Color::white();
+        let background = Background::Gradient { top, bottom };
+        let origin = Point::new(0.0, 0.0, 0.0);
+        let up = Ray::new(origin, Vector::new(0.0, 1.0, 0.0));
+        let down = Ray::new(origin, Vector::new(0.0, -1.0, 0.0));
+        let level = Ray::new(origin, Vector::new(0.0, 0.0, 1.0));
+        assert_eq!(background.color_at(up), top);
+        assert_eq!(background.color_at(down), bottom);
+        assert_eq!(background.color_at(level), Color::new(0.75, 0.85, 1.0));
+    }
+
+    #[test]
+    fn environment_map_is_sampled_by_ray_direction() {
+        let mut image = Canvas::new(5, 3);
+        for y in 0..3 {
+            for x in 0..5 {
+                image.write_pixel(x, y, &Color::new(x as f64 / 4.0, y as f64 / 2.0, 0.0));
+            }
+        }
+        let background = Background::EnvironmentMap(Arc::new(image));
+        let origin = Point::new(0.0, 0.0, 0.0);
+        let cases = [
+            (Vector::new(0.0, 0.0, -1.0), Color::new(0.0, 0.5, 0.0)),
+            (Vector::new(1.0, 0.0, 0.0), Color::new(0.25, 0.5, 0.0)),
+            (Vector::new(0.0, 0.0, 1.0), Color::new(0.5, 0.5, 0.0)),
+            (Vector::new(-1.0, 0.0, 0.0), Color::new(0.75, 0.5, 0.0)),
+            (Vector::new(0.0, 1.0, 0.0), Color::new(0.5, 0.0, 0.0)),
+            (Vector::new(0.0, -1.0, 0.0), Color::new(0.5, 1.0, 0.0)),
+        ];
+        for (direction, expected) in cases {
+            assert_eq!(background.color_at(Ray::new(origin, direction)), expected);
+        }
+    }
+
+    #[test]
+    fn empty_environment_map_is_black() {
+        let background = Background::EnvironmentMap(Arc::new(Canvas::new(0, 0)));
+        let ray = Ray::new(Point::new(0.0, 0.0, 0.0), Vector::new(0.0, 0.0, -1.0));
+        assert_eq!(background.color_at(ray), Color::black());
+    }
+
+    #[test]
+    fn emissive_surface_glows_without_any_light() {
+        let glow = Color::new(1.0, 0.8, 0.2);
+        let mut sphere = Object::Sphere(Sphere::new());
+        sphere.set_material(Material {
+            emission: glow,
+            ..Default::default()
+        });
+        let world = World {
+            objects: vec![sphere],
+            lights: Vec::new(),
+            ..Default::default()
+        };
+        let ray = Ray::new(Point::new(0.0, 0.0, -5.0), Vector::new(0.0, 0.0, 1.0));
+        assert_eq!(world.color_at(ray), glow);
+    }
+
+    #[test]
+    fn emission_is_added_on_top_of_lighting() {
+        let world = World::default();
+        let mut objects = world.objects;
+        let material = objects[0].material();
+        objects[0].set_material(Material {
+            emission: Color::new(0.1, 0.1, 0.1),
+            ..material
+        });
+        let world = World {
+            objects,
+            ..Default::default()
+        };
+        let ray = Ray::new(Point::new(0.0, 0.0, -5.0), Vector::new(0.0, 0.0, 1.0));
+        assert_eq!(world.color_at(ray), Color::new(0.48066, 0.57583, 0.3855));
+    }
+
+    #[test]
+    fn objects_that_do_not_cast_shadows_are_ignored() {
+        let world = World::default();
+        let mut objects = world.objects;
+        for object in objects.iter_mut() {
+            let material = object.material();
+            object.set_material(Material {
+                casts_shadow: false,
+                ..material
+            });
+        }
+        let world = World {
+            objects,
+            ..Default::default()
+        };
+        let light_position = world.light_source().unwrap().position().unwrap();
+        let point = Point::new(10.0, -10.0, 10.0);
+        assert!(!world.is_shadowed(light_position, point));
+    }
+
+    #[test]
+    fn moving_objects_cast_shadows_where_they_are_at_the_ray_time() {
+        let mut occluder = Object::sphere_builder()
+            .transform(Matrix4::translate(0.0, 5.0, 0.0))
+            .build();
+        occluder.set_motion(Matrix4::identity());
+        let mut world = World::new();
+        world.add_object(occluder);
+        let light = Light::from(PointLight::new(Point::new(-10.0, 0.0, 0.0), Color::white()));
+        let point = Point::new(10.0, 0.0, 0.0);
+        assert_eq!(world.intensity_at_time(&light, point, 0.0), 1.0);
+        assert_eq!(world.intensity_at_time(&light, point, 1.0), 0.0);
+
+        // Hits remember the time of the ray, for shading to cast its shadow rays at
+        let shape = Object::Sphere(Sphere::with_transform(Matrix4::translate(11.0, 0.0, 0.0)));
+        let ray = Ray::at_time(Point::new(5.0, 0.0, 0.0), Vector::new(1.0, 0.0, 0.0), 1.0);
+        let comps = Intersection::new(5.0, &shape).prepare_computations(ray);
+        assert_eq!(comps.time_of_ray, 1.0);
+    }
+
+    #[test]
+    fn adding_and_removing_objects() {
+        let mut world = World::new();
+        let first = world.add_object(Object::Sphere(Sphere::new()));
+        let second = world.add_object(Object::Sphere(Sphere::with_transform(Matrix4::translate(
+            0.0, 2.0, 0.0,
+        ))));
+        assert_eq!(first, 0);
+        assert_eq!(second, 1);
+        assert_eq!(
+            world.get_object(first),
+            Some(&Object::Sphere(Sphere::new()))
+        );
+
+        world
+            .get_object_mut(second)
+            .unwrap()
+            .set_transform(Matrix4::translate(0.0, 3.0, 0.0));
+        assert_eq!(
+            world.get_object(second).unwrap().transformation(),
+            Matrix4::translate(0.0, 3.0, 0.0)
+        );
+
+        assert_eq!(
+            world.remove_object(first),
+            Some(Object::Sphere(Sphere::new()))
+        );
+        assert_eq!(world.objects.len(), 1);
+        assert_eq!(world.remove_object(5), None);
+        assert_eq!(world.get_object(1), None);
+    }
+
+    fn neighboring_spheres() -> World {
+        World {
+            objects: vec![
+                Object::Sphere(Sphere::new()),
+                Object::Sphere(Sphere::with_transform(Matrix4::translate(2.5, 0.0, 0.0))),
+            ],
+            ambient_occlusion: Some(AoConfig {
+                samples: 32,
+                radius: 2.0,
+            }),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn ambient_occlusion_is_off_by_default() {
+        let world = World::default();
+        assert_eq!(world.ambient_occlusion, None);
+        let world = World {
+            ambient_occlusion: None,
+            ..neighboring_spheres()
+        };
+        let visibility =
+            world.ambient_visibility(Point::new(1.0, 0.0, 0.0), Vector::new(1.0, 0.0, 0.0));
+        assert_eq!(visibility, 1.0);
+    }
+
+    #[test]
+    fn ambient_visibility_drops_where_objects_meet() {
+        let world = neighboring_spheres();
+        let open =
+            world.ambient_visibility(Point::new(-1.0001, 0.0, 0.0), Vector::new(-1.0, 0.0, 0.0));
+        let crease =
+            world.ambient_visibility(Point::new(1.0001, 0.0, 0.0), Vector::new(1.0, 0.0, 0.0));
+        assert_eq!(open, 1.0);
+        // The neighbor fills a cone of about 42 degrees, which blocks around 45% of the
+        // cosine-weighted samples
+        assert!(crease > 0.45 && crease < 0.65);
+    }
+
+    #[test]
+    fn ambient_occlusion_only_darkens_the_ambient_term() {
+        let world = neighboring_spheres();
+        let ray = Ray::new(Point::new(0.8, 0.0, 5.0), Vector::new(0.0, 0.0, -1.0));
+        let occluded = world.color_at(ray);
+        let unoccluded = World {
+            ambient_occlusion: None,
+            ..neighboring_spheres()
+        }
+        .color_at(ray);
+        let difference = unoccluded - occluded;
+        assert!(difference.red > 0.0);
+        // Never more than the full ambient contribution of 0.1
+        assert!(difference.red <= 0.1);
+    }
+
+    #[test]
+    fn directional_light_casts_parallel_shadows() {
+        let sun = Light::from(DirectionalLight::new(
+            Vector::new(0.0, -1.0, 0.0),
+            Color::white(),
+        ));
+        let world = World {
+            lights: vec![sun],
+            ..Default::default()
+        };
+        // However far below the sphere, a point in its column is shadowed
+        assert_eq!(world.intensity_at(&sun, Point::new(0.5, -2.0, 0.0)), 0.0);
+        assert_eq!(world.intensity_at(&sun, Point::new(0.5, -1000.0, 0.0)), 0.0);
+        assert_eq!(world.intensity_at(&sun, Point::new(1.5, -2.0, 0.0)), 1.0);
+    }
+
+    #[test]
+    fn directional_light_shading_ignores_distance() {
+        let material = Material::default();
+        let sun = Light::from(DirectionalLight::new(
+            Vector::new(0.0, 0.0, 1.0),
+            Color::white(),
+        ));
+        let eye_vector = Vector::new(0.0, 0.0, -1.0);
+        let normal_vector = Vector::new(0.0, 0.0, -1.0);
+        for point in [Point::new(0.0, 0.0, 0.0), Point::new(50.0, -20.0, 7.0)] {
+            let result = material.lighting(sun, point, eye_vector, normal_vector, 1.0);
+            assert_eq!(result, Color::new(1.9, 1.9, 1.9));
+        }
+    }
+
+    #[test]
+    fn fog_fades_colors_with_distance() {
+        let fog = Fog {
+            color: Color::new(0.5, 0.5, 0.5),
+            density: 0.5,
+        };
+        let red = Color::new(1.0, 0.0, 0.0);
+        assert_eq!(fog.apply(red, 0.0), red);
+        let amount = 1.0 - (-1.0_f64).exp();
+        assert_eq!(
+            fog.apply(red, 2.0),
+            Color::new(1.0 - 0.5 * amount, 0.5 * amount, 0.5 * amount)
+        );
+        assert_eq!(fog.apply(red, 1000.0), fog.color);
+    }
+
+    #[test]
+    fn fog_blends_hits_and_replaces_misses() {
+        let fog = Fog {
+            color: Color::new(0.5, 0.5, 0.5),
+            density: 0.1,
+        };
+        let world = World {
+            fog: Some(fog),
+            ..Default::default()
+        };
+        let ray = Ray::new(Point::new(0.0, 0.0, -5.0), Vector::new(0.0, 0.0, 1.0));
+        let surface = Color::new(0.38066, 0.47583, 0.2855);
+        assert_eq!(world.color_at(ray), fog.apply(surface, 4.0));
+
+        let miss = Ray::new(Point::new(0.0, 0.0, -5.0), Vector::new(0.0, 1.0, 0.0));
+        assert_eq!(world.color_at(miss), fog.color);
+    }
 }